@@ -0,0 +1,22 @@
+use cache_benchmarks::{
+    generate_cyclic_trace, generate_skewed_trace, hit_ratios_relative_to_belady,
+};
+
+fn report(workload: &str, max_capacity: usize, trace: &[usize]) {
+    println!(
+        "{workload} (max_capacity: {max_capacity}, requests: {})",
+        trace.len()
+    );
+    for (registry, ratio) in hit_ratios_relative_to_belady(max_capacity, trace) {
+        println!(
+            "  {registry:<12} {:>6.1}% of the optimal hits",
+            ratio * 100.0
+        );
+    }
+}
+
+/// Reports the hit ratio of each registry relative to Belady's optimal bound.
+fn main() {
+    report("skewed", 1_000, &generate_skewed_trace(100_000, 10_000, 42));
+    report("cyclic", 1_000, &generate_cyclic_trace(100_000, 1_100));
+}
@@ -1,3 +1,5 @@
 pub(crate) mod benches;
 pub(crate) mod concurrent_queued_cache;
+pub(crate) mod hit_ratio;
 pub(crate) mod queued_cache;
+pub(crate) mod random_cache;
@@ -0,0 +1,44 @@
+use cache_benchmarks::{
+    queued_cache_sequential, queued_lookup_cache_sequential, random_cache_parallel,
+    random_cache_sequential,
+};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+criterion_group!(
+    benches,
+    criterion_random_cache_comparison,
+    criterion_random_cache_parallel,
+);
+criterion_main!(benches);
+
+pub fn criterion_random_cache_comparison(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Compare the random cache baseline to queued caches");
+
+    group.bench_function(
+        "randomCache: n_keys: high, value_size: low, max_capacity: high",
+        |b| b.iter(|| random_cache_sequential(black_box((10_000, 20_000, 2)))),
+    );
+
+    group.bench_function(
+        "queuedCache - v1: n_keys: high, value_size: low, max_capacity: high",
+        |b| b.iter(|| queued_cache_sequential(black_box((10_000, 20_000, 2)))),
+    );
+
+    group.bench_function(
+        "queuedCache - v2: n_keys: high, value_size: low, max_capacity: high",
+        |b| b.iter(|| queued_lookup_cache_sequential(black_box((10_000, 20_000, 2)))),
+    );
+
+    group.finish()
+}
+
+pub fn criterion_random_cache_parallel(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Concurrent Random Cache");
+
+    group.bench_function(
+        "Random: n_keys: high, value_size: low, max_capacity: high",
+        |b| b.iter(|| random_cache_parallel(black_box((5_000, 10_000, 2)))),
+    );
+
+    group.finish()
+}
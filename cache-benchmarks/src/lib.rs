@@ -4,13 +4,33 @@ extern crate modular_cache;
 use std::sync::Arc;
 
 use modular_cache::{
-    cache::{Cache, GetKey, KeyRegistry},
+    arc_cache::ArcCache,
+    belady_cache::{BeladyCache, BeladyRegistry},
+    cache::{Cache, GetKey, GetKeyMut, KeyRegistry},
+    car_cache::CarCache,
+    clock_cache::ClockCache,
     concurrent_cache::ConcurrentCache,
+    gdsf_cache::{GdsfCache, Weigher},
+    hyperbolic_cache::HyperbolicCache,
+    key::TimedKey,
+    lecar_cache::LecarCache,
+    lfu_cache::LfuCache,
+    lirs_cache::LirsCache,
+    lru_cache::LruCache,
+    lru_k_cache::LruKCache,
+    mru_cache::MruCache,
     queued_cache::{
         ConcurrentQueuedCache, ConcurrentQueuedLookupCache, QueuedCache, QueuedLookupCache,
     },
+    random_cache::{ConcurrentRandomCache, RandomCache},
+    s3_fifo_cache::S3FifoCache,
+    sampled_cache::{SampledCache, SampledConfig, SampledPolicy, SampledRegistry},
+    sieve_cache::SieveCache,
+    slru_cache::SlruCache,
+    tiny_lfu_cache::TinyLfuCache,
+    two_queue_cache::TwoQueueCache,
 };
-use rand::Rng;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 // use rand::Rng;
 
 // // Poem by Friedrich Schiller. The corresponding music is the European Anthem.
@@ -54,7 +74,7 @@ fn create_value(value_len: usize) -> String {
     v.join("")
 }
 
-/// Creates a collection of at most 32 keys
+// Creates a collection of at most 32 keys
 // pub(crate) fn gernerate_key_values(n_keys: usize, value_len: usize) -> Vec<(String, String)> {
 //     let mut key_values = Vec::with_capacity(n_keys);
 //     let mut rng = rand::thread_rng();
@@ -93,7 +113,7 @@ fn insert_and_get_seq<R>(
     let key_values = gernerate_key_values(n_keys, value_len);
 
     for (k, v) in key_values.iter() {
-        cache.insert(k.clone(), v.clone());
+        cache.insert(*k, v.clone());
     }
 
     let mut rng = rand::thread_rng();
@@ -116,6 +136,12 @@ pub fn queued_lookup_cache_sequential((max_capacity, n_keys, value_len): (usize,
     assert!(cache.len() <= max_capacity);
 }
 
+pub fn random_cache_sequential((max_capacity, n_keys, value_len): (usize, usize, usize)) {
+    let mut cache = RandomCache::<usize, usize, String>::new(Some(max_capacity));
+    insert_and_get_seq(&mut cache, n_keys, value_len);
+    assert!(cache.len() <= max_capacity);
+}
+
 fn insert_and_get_concurrent<R>(
     cache: Arc<ConcurrentCache<usize, R, usize, String>>,
     n_keys: usize,
@@ -126,7 +152,7 @@ fn insert_and_get_concurrent<R>(
     let key_values = gernerate_key_values(n_keys, value_len);
 
     for (k, v) in key_values.iter() {
-        cache.insert(k.clone(), v.clone());
+        cache.insert(*k, v.clone());
     }
 
     let mut rng = rand::thread_rng();
@@ -172,3 +198,172 @@ pub fn queued_lookup_cache_parallel((max_capacity, n_keys, value_len): (usize, u
 
     assert!(cache.len() <= max_capacity);
 }
+
+pub fn random_cache_parallel((max_capacity, n_keys, value_len): (usize, usize, usize)) {
+    let cache = Arc::new(ConcurrentRandomCache::<usize, usize, String>::new(Some(
+        max_capacity,
+    )));
+
+    let mut handles = Vec::new();
+    for _ in 0..2 {
+        let thread_cache = cache.clone();
+        handles.push(std::thread::spawn(move || {
+            insert_and_get_concurrent(thread_cache, n_keys, value_len)
+        }));
+    }
+
+    for h in handles {
+        h.join().unwrap();
+    }
+
+    assert!(cache.len() <= max_capacity);
+}
+
+/// Generates `n_requests` requests of `n_keys` keys, where small keys are requested more often.
+/// The same `seed` generates the same trace.
+pub fn generate_skewed_trace(n_requests: usize, n_keys: usize, seed: u64) -> Vec<usize> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..n_requests)
+        .map(|_| (rng.gen::<f64>().powi(3) * n_keys as f64) as usize)
+        .collect()
+}
+
+/// Generates `n_requests` requests cycling through `n_keys` keys.
+pub fn generate_cyclic_trace(n_requests: usize, n_keys: usize) -> Vec<usize> {
+    (0..n_requests).map(|idx| idx % n_keys).collect()
+}
+
+fn count_hits<R>(mut cache: Cache<usize, R, usize, usize>, trace: &[usize]) -> usize
+where
+    R: KeyRegistry<usize, KeyExtension = usize> + GetKeyMut<usize>,
+{
+    let mut hits = 0;
+    for &k in trace {
+        match cache.get_mut(&k) {
+            Some(_) => hits += 1,
+            None => _ = cache.insert(k, k),
+        }
+    }
+    hits
+}
+
+fn count_hits_without_stats<R>(mut cache: Cache<usize, R, usize, usize>, trace: &[usize]) -> usize
+where
+    R: KeyRegistry<usize, KeyExtension = usize> + GetKey<usize>,
+{
+    let mut hits = 0;
+    for &k in trace {
+        match cache.get(&k) {
+            Some(_) => hits += 1,
+            None => _ = cache.insert(k, k),
+        }
+    }
+    hits
+}
+
+fn count_timed_hits<R>(mut cache: Cache<usize, R, TimedKey<usize>, usize>, trace: &[usize]) -> usize
+where
+    R: KeyRegistry<usize, KeyExtension = TimedKey<usize>> + GetKeyMut<usize>,
+{
+    let mut hits = 0;
+    for &k in trace {
+        match cache.get_mut(&k) {
+            Some(_) => hits += 1,
+            None => _ = cache.insert(TimedKey::create_now(k), k),
+        }
+    }
+    hits
+}
+
+/// Weighs every key the same, such that size-aware registries only count keys.
+#[derive(Default)]
+struct UnitWeigher;
+
+impl Weigher<usize> for UnitWeigher {
+    fn size(&self, _key: &usize) -> usize {
+        1
+    }
+}
+
+/// The hit ratio of each registry on the `trace` relative to the optimal one of
+/// [`BeladyRegistry`], i.e. 1.0 means there is no headroom left.
+pub fn hit_ratios_relative_to_belady(
+    max_capacity: usize,
+    trace: &[usize],
+) -> Vec<(&'static str, f64)> {
+    let belady_registry = BeladyRegistry::with_trace(max_capacity, trace.iter().copied());
+    let optimal_hits = count_hits(BeladyCache::with_registry(belady_registry), trace);
+    let relative = |hits: usize| match optimal_hits {
+        0 => 1.0,
+        _ => hits as f64 / optimal_hits as f64,
+    };
+    let cap = Some(max_capacity);
+
+    vec![
+        (
+            "Queued",
+            relative(count_hits_without_stats(QueuedCache::new(cap), trace)),
+        ),
+        (
+            "QueuedLookup",
+            relative(count_hits_without_stats(QueuedLookupCache::new(cap), trace)),
+        ),
+        (
+            "Random",
+            relative(count_hits_without_stats(RandomCache::new(cap), trace)),
+        ),
+        ("LRU", relative(count_hits(LruCache::new(cap), trace))),
+        ("MRU", relative(count_hits(MruCache::new(cap), trace))),
+        ("LFU", relative(count_hits(LfuCache::new(cap), trace))),
+        ("LRU-K", relative(count_hits(LruKCache::new(cap), trace))),
+        ("SLRU", relative(count_hits(SlruCache::new(cap), trace))),
+        ("2Q", relative(count_hits(TwoQueueCache::new(cap), trace))),
+        ("ARC", relative(count_hits(ArcCache::new(cap), trace))),
+        ("CAR", relative(count_hits(CarCache::new(cap), trace))),
+        ("LIRS", relative(count_hits(LirsCache::new(cap), trace))),
+        ("CLOCK", relative(count_hits(ClockCache::new(cap), trace))),
+        ("SIEVE", relative(count_hits(SieveCache::new(cap), trace))),
+        (
+            "S3-FIFO",
+            relative(count_hits(S3FifoCache::new(cap), trace)),
+        ),
+        (
+            "W-TinyLFU",
+            relative(count_hits(TinyLfuCache::new(cap), trace)),
+        ),
+        ("LeCaR", relative(count_hits(LecarCache::new(cap), trace))),
+        (
+            "GDSF",
+            relative(count_hits(
+                GdsfCache::<_, _, _, UnitWeigher>::new(cap),
+                trace,
+            )),
+        ),
+        (
+            "Hyperbolic",
+            relative(count_timed_hits(HyperbolicCache::new(cap), trace)),
+        ),
+        (
+            "Sampled LRU",
+            relative(count_hits(
+                sampled_cache(max_capacity, SampledPolicy::Lru),
+                trace,
+            )),
+        ),
+        (
+            "Sampled LFU",
+            relative(count_hits(
+                sampled_cache(max_capacity, SampledPolicy::Lfu),
+                trace,
+            )),
+        ),
+    ]
+}
+
+fn sampled_cache(max_capacity: usize, policy: SampledPolicy) -> SampledCache<usize, usize, usize> {
+    let config = SampledConfig {
+        policy,
+        ..SampledConfig::default()
+    };
+    SampledCache::with_registry(SampledRegistry::with_config(max_capacity, config))
+}
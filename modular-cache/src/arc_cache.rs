@@ -0,0 +1,308 @@
+use crate::{
+    cache::{preallocation, Cache, GetKeyMut, KeyRegistry},
+    concurrent_cache::ConcurrentCache,
+    key::KeyExtension,
+    linked_list::LinkedList,
+};
+use std::{collections::HashMap, hash::Hash};
+
+#[derive(Debug, Clone, Copy)]
+enum Location {
+    T1(usize),
+    T2(usize),
+    B1(usize),
+    B2(usize),
+}
+
+/// [`ARC`](https://en.wikipedia.org/wiki/Adaptive_replacement_cache) registry,
+/// balancing between recency (T1) and frequency (T2) of the resident keys.
+/// The ghost lists B1 and B2 remember keys recently evicted from T1 and T2, respectively;
+/// a hit on them adapts the target size `p` of T1.
+/// Takes O(1) for finding, promoting and removing keys.
+#[derive(Debug)]
+pub struct ArcRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+{
+    lookup: HashMap<K, Location>,
+    /// resident keys seen once recently, most recently used in front
+    t1: LinkedList<KeyExt>,
+    /// resident keys seen at least twice recently, most recently used in front
+    t2: LinkedList<KeyExt>,
+    /// ghost keys evicted from t1, most recently evicted in front
+    b1: LinkedList<K>,
+    /// ghost keys evicted from t2, most recently evicted in front
+    b2: LinkedList<K>,
+    /// target size of t1
+    p: usize,
+    b1_hits: usize,
+    b2_hits: usize,
+    max_capacity: usize,
+}
+
+impl<KeyExt, K> ArcRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + Clone,
+{
+    /// The current target size of T1, the list of keys seen once recently.
+    pub fn p(&self) -> usize {
+        self.p
+    }
+
+    /// The number of re-added keys which were found in the ghost list B1.
+    pub fn b1_hits(&self) -> usize {
+        self.b1_hits
+    }
+
+    /// The number of re-added keys which were found in the ghost list B2.
+    pub fn b2_hits(&self) -> usize {
+        self.b2_hits
+    }
+
+    fn demote_t1(&mut self) -> Option<K> {
+        let k = self.t1.pop_back()?.key().clone();
+        let idx = self.b1.push_front(k.clone());
+        self.lookup.insert(k.clone(), Location::B1(idx));
+        Some(k)
+    }
+
+    fn demote_t2(&mut self) -> Option<K> {
+        let k = self.t2.pop_back()?.key().clone();
+        let idx = self.b2.push_front(k.clone());
+        self.lookup.insert(k.clone(), Location::B2(idx));
+        Some(k)
+    }
+
+    /// The `REPLACE` routine of ARC: moves the LRU key of either T1 or T2 into its ghost list.
+    fn replace(&mut self, in_b2: bool) -> Option<K> {
+        let t1_len = self.t1.len();
+        if t1_len >= 1 && ((in_b2 && t1_len == self.p) || t1_len > self.p) {
+            self.demote_t1().or_else(|| self.demote_t2())
+        } else {
+            self.demote_t2().or_else(|| self.demote_t1())
+        }
+    }
+
+    fn replace_if_full(&mut self, in_b2: bool) -> Option<K> {
+        if self.len() >= self.max_capacity {
+            self.replace(in_b2)
+        } else {
+            None
+        }
+    }
+
+    fn forget_b1(&mut self) {
+        if let Some(k) = self.b1.pop_back() {
+            self.lookup.remove(&k);
+        }
+    }
+
+    fn forget_b2(&mut self) {
+        if let Some(k) = self.b2.pop_back() {
+            self.lookup.remove(&k);
+        }
+    }
+
+    fn insert_t2(&mut self, key: KeyExt) {
+        let k = key.key().clone();
+        let idx = self.t2.push_front(key);
+        self.lookup.insert(k, Location::T2(idx));
+    }
+}
+
+impl<KeyExt, K> GetKeyMut<K> for ArcRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Eq + Hash,
+{
+    /// Takes O(1) for finding the key and promoting it to the front of T2.
+    fn get(&mut self, key: &K) -> Option<&K> {
+        let location = self.lookup.get_mut(key)?;
+        match *location {
+            Location::T1(idx) => {
+                let tk = self.t1.remove(idx);
+                let idx = self.t2.push_front(tk);
+                *location = Location::T2(idx);
+                Some(self.t2.get(idx).key())
+            }
+            Location::T2(idx) => {
+                self.t2.move_to_front(idx);
+                Some(self.t2.get(idx).key())
+            }
+            Location::B1(_) | Location::B2(_) => None,
+        }
+    }
+}
+
+impl<KeyExt, K> KeyRegistry<K> for ArcRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + PartialEq + Clone,
+{
+    type KeyExtension = KeyExt;
+
+    fn with_capacity(max_capacity: usize) -> Self {
+        Self {
+            lookup: HashMap::with_capacity(preallocation(max_capacity)),
+            t1: LinkedList::with_capacity(preallocation(max_capacity)),
+            t2: LinkedList::with_capacity(preallocation(max_capacity)),
+            b1: LinkedList::with_capacity(preallocation(max_capacity)),
+            b2: LinkedList::with_capacity(preallocation(max_capacity)),
+            p: 0,
+            b1_hits: 0,
+            b2_hits: 0,
+            max_capacity,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.lookup.clear();
+        self.t1.clear();
+        self.t2.clear();
+        self.b1.clear();
+        self.b2.clear();
+        self.p = 0;
+        self.b1_hits = 0;
+        self.b2_hits = 0;
+    }
+
+    fn len(&self) -> usize {
+        self.t1.len() + self.t2.len()
+    }
+
+    /// Takes O(1); an already present key is updated and promoted as on a hit.
+    fn add_or_update(&mut self, key: Self::KeyExtension) -> Option<K> {
+        match self.lookup.get(key.key()).copied() {
+            Some(Location::T1(idx)) => {
+                self.t1.remove(idx);
+                self.insert_t2(key);
+                None
+            }
+            Some(Location::T2(idx)) => {
+                *self.t2.get_mut(idx) = key;
+                self.t2.move_to_front(idx);
+                None
+            }
+            Some(Location::B1(idx)) => {
+                self.b1_hits += 1;
+                let delta = (self.b2.len() / self.b1.len()).max(1);
+                self.p = self.p.saturating_add(delta).min(self.max_capacity);
+                self.b1.remove(idx);
+                let deleted_key = self.replace_if_full(false);
+                self.insert_t2(key);
+                deleted_key
+            }
+            Some(Location::B2(idx)) => {
+                self.b2_hits += 1;
+                let delta = (self.b1.len() / self.b2.len()).max(1);
+                self.p = self.p.saturating_sub(delta);
+                self.b2.remove(idx);
+                let deleted_key = self.replace_if_full(true);
+                self.insert_t2(key);
+                deleted_key
+            }
+            None => {
+                let l1_len = self.t1.len() + self.b1.len();
+                let total_len = l1_len + self.t2.len() + self.b2.len();
+                let deleted_key = if self.t1.len() >= self.max_capacity {
+                    self.t1.pop_back().map(|tk| {
+                        self.lookup.remove(tk.key());
+                        tk.key().clone()
+                    })
+                } else {
+                    if l1_len >= self.max_capacity {
+                        self.forget_b1();
+                    } else if total_len >= self.max_capacity.saturating_mul(2) {
+                        self.forget_b2();
+                    }
+                    self.replace_if_full(false)
+                };
+
+                let k = key.key().clone();
+                let idx = self.t1.push_front(key);
+                self.lookup.insert(k, Location::T1(idx));
+                deleted_key
+            }
+        }
+    }
+
+    /// Takes O(1); ghost keys are not affected.
+    fn try_remove(&mut self, key: &K) -> Option<K> {
+        let tk = match self.lookup.get(key).copied()? {
+            Location::T1(idx) => self.t1.remove(idx),
+            Location::T2(idx) => self.t2.remove(idx),
+            Location::B1(_) | Location::B2(_) => return None,
+        };
+        self.lookup.remove(key);
+        Some(tk.key().clone())
+    }
+}
+
+pub type ArcCache<K, KeyExt, V> = Cache<K, ArcRegistry<KeyExt, K>, KeyExt, V>;
+pub type ConcurrentArcCache<K, KeyExt, V> = ConcurrentCache<K, ArcRegistry<KeyExt, K>, KeyExt, V>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arc_cache_init() {
+        let mut cache = ArcCache::<i32, i32, String>::new(Some(4));
+        cache.insert(1, "How".to_string());
+        cache.insert(2, "Hi".to_string());
+        cache.insert(3, "Are".to_string());
+        cache.insert(4, "You".to_string());
+        cache.insert(5, "Doing".to_string());
+        cache.insert(2, "How".to_string());
+
+        assert_eq!(cache.len(), 4);
+
+        assert_eq!(cache.get_mut(&1), None);
+        assert_eq!(cache.get_mut(&2).cloned(), Some("How".to_string()));
+        assert_eq!(cache.get_mut(&3).cloned(), Some("Are".to_string()));
+        assert_eq!(cache.get_mut(&4).cloned(), Some("You".to_string()));
+        assert_eq!(cache.get_mut(&5).cloned(), Some("Doing".to_string()));
+    }
+
+    #[test]
+    fn arc_cache_exposes_diagnostics() {
+        let mut cache = ArcCache::<i32, i32, String>::new(Some(2));
+        cache.insert(1, "How".to_string());
+        cache.insert(2, "Hi".to_string());
+        cache.get_mut(&2);
+        cache.insert(3, "Are".to_string());
+
+        // re-adding the evicted key 1 is a hit in the ghost list B1
+        cache.insert(1, "How".to_string());
+        assert_eq!(cache.key_registry().b1_hits(), 1);
+        assert_eq!(cache.key_registry().p(), 1);
+    }
+
+    #[test]
+    fn arc_registry_adapts_on_ghost_hits() {
+        let mut registry = ArcRegistry::<i32, i32>::with_capacity(2);
+        registry.add_or_update(1);
+        registry.get(&1);
+        registry.add_or_update(2);
+
+        // t1 = [2], t2 = [1]; 2 is moved to b1
+        assert_eq!(registry.add_or_update(3), Some(2));
+        assert_eq!(registry.p(), 0);
+
+        // ghost hit in b1 grows t1's target and replaces from t2
+        assert_eq!(registry.add_or_update(2), Some(1));
+        assert_eq!(registry.p(), 1);
+        assert_eq!(registry.b1_hits(), 1);
+
+        // ghost hit in b2 shrinks t1's target and replaces from t1
+        assert_eq!(registry.add_or_update(1), Some(3));
+        assert_eq!(registry.p(), 0);
+        assert_eq!(registry.b2_hits(), 1);
+
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.try_remove(&3), None);
+        assert_eq!(registry.try_remove(&1), Some(1));
+        assert_eq!(registry.len(), 1);
+    }
+}
@@ -0,0 +1,221 @@
+use crate::{
+    cache::{preallocation, Cache, GetKeyMut, KeyRegistry},
+    concurrent_cache::ConcurrentCache,
+    key::KeyExtension,
+};
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    hash::Hash,
+};
+
+/// Position in the eviction order: the next use of a key and a counter breaking ties among keys
+/// which are not used again.
+type Rank = (usize, u64);
+
+#[derive(Debug)]
+struct BeladyEntry<KeyExt> {
+    key: KeyExt,
+    rank: Rank,
+}
+
+/// [`Bélády's optimal`](https://en.wikipedia.org/wiki/Cache_replacement_policies#B%C3%A9l%C3%A1dy's_algorithm)
+/// registry, evicting the key whose next use is furthest away in a known trace of future requests.
+/// This includes the added key itself, which is rejected if it is used later than all others.
+/// Not meant for production, but as upper bound of the hit ratio when simulating a trace: each
+/// `get` consumes the next request, which is expected to follow the trace, and a miss is followed
+/// by `add_or_update` of the requested key. Without a trace no key is ever used again.
+/// Takes O(log n) for finding, adding and removing keys.
+#[derive(Debug)]
+pub struct BeladyRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+{
+    entries: HashMap<K, BeladyEntry<KeyExt>>,
+    /// keys ordered by their next use in ASC order
+    ranks: BTreeMap<Rank, K>,
+    /// the positions at which each key is requested from the current position on
+    future: HashMap<K, VecDeque<usize>>,
+    /// the number of consumed requests
+    position: usize,
+    counter: u64,
+    max_capacity: usize,
+}
+
+impl<KeyExt, K> BeladyRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + Clone,
+{
+    /// Creates a registry for the `trace` of all requests which are going to be made.
+    pub fn with_trace<I>(max_capacity: usize, trace: I) -> Self
+    where
+        I: IntoIterator<Item = K>,
+    {
+        let mut future: HashMap<K, VecDeque<usize>> = HashMap::new();
+        for (position, k) in trace.into_iter().enumerate() {
+            future.entry(k).or_default().push_back(position);
+        }
+        Self {
+            entries: HashMap::with_capacity(preallocation(max_capacity)),
+            ranks: BTreeMap::new(),
+            future,
+            position: 0,
+            counter: 0,
+            max_capacity,
+        }
+    }
+
+    fn rank(&mut self, key: &K) -> Rank {
+        self.counter += 1;
+        let next_use = self
+            .future
+            .get(key)
+            .and_then(|positions| positions.front())
+            .copied()
+            .unwrap_or(usize::MAX);
+        (next_use, self.counter)
+    }
+
+    /// Moves past the current request of `key`.
+    fn consume(&mut self, key: &K) {
+        self.position += 1;
+        if let Some(positions) = self.future.get_mut(key) {
+            while positions.front().is_some_and(|&p| p < self.position) {
+                positions.pop_front();
+            }
+            if positions.is_empty() {
+                self.future.remove(key);
+            }
+        }
+    }
+}
+
+impl<KeyExt, K> GetKeyMut<K> for BeladyRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Eq + Hash + Clone,
+{
+    /// Takes O(log n) for consuming the request and updating the key's next use.
+    fn get(&mut self, key: &K) -> Option<&K> {
+        self.consume(key);
+        let rank = self.entries.get(key)?.rank;
+        let new_rank = self.rank(key);
+        if let Some(k) = self.ranks.remove(&rank) {
+            self.ranks.insert(new_rank, k);
+        }
+
+        let entry = self.entries.get_mut(key)?;
+        entry.rank = new_rank;
+        Some(entry.key.key())
+    }
+}
+
+impl<KeyExt, K> KeyRegistry<K> for BeladyRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + PartialEq + Clone,
+{
+    type KeyExtension = KeyExt;
+
+    fn with_capacity(max_capacity: usize) -> Self {
+        Self::with_trace(max_capacity, Vec::new())
+    }
+
+    /// Removes all keys, but does not rewind the trace.
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.ranks.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Takes O(log n); an already present key is updated in place.
+    fn add_or_update(&mut self, key: Self::KeyExtension) -> Option<K> {
+        if let Some(entry) = self.entries.get_mut(key.key()) {
+            entry.key = key;
+            return None;
+        }
+
+        let k = key.key().clone();
+        let rank = self.rank(&k);
+        let deleted_key = if self.len() >= self.max_capacity {
+            match self.ranks.last_key_value() {
+                Some((&(furthest, _), _)) if furthest >= rank.0 => {
+                    self.ranks.pop_last().map(|(_, k)| {
+                        self.entries.remove(&k);
+                        k
+                    })
+                }
+                _ => return Some(k),
+            }
+        } else {
+            None
+        };
+
+        self.ranks.insert(rank, k.clone());
+        self.entries.insert(k, BeladyEntry { key, rank });
+        deleted_key
+    }
+
+    /// Takes O(log n).
+    fn try_remove(&mut self, key: &K) -> Option<K> {
+        let entry = self.entries.remove(key)?;
+        self.ranks.remove(&entry.rank);
+        Some(entry.key.key().clone())
+    }
+}
+
+pub type BeladyCache<K, KeyExt, V> = Cache<K, BeladyRegistry<KeyExt, K>, KeyExt, V>;
+pub type ConcurrentBeladyCache<K, KeyExt, V> =
+    ConcurrentCache<K, BeladyRegistry<KeyExt, K>, KeyExt, V>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lru_cache::LruCache;
+
+    #[test]
+    fn belady_registry_evicts_furthest_next_use() {
+        let trace = [1, 2, 3, 1, 2, 4, 1, 3];
+        let mut registry = BeladyRegistry::<i32, i32>::with_trace(2, trace);
+        let mut evictions = Vec::new();
+
+        for k in trace {
+            if registry.get(&k).is_none() {
+                evictions.push(registry.add_or_update(k));
+            }
+        }
+
+        // 3 is rejected as it is used later than 1 and 2, then 2 and 1 are not used again
+        assert_eq!(evictions, vec![None, None, Some(3), Some(2), Some(1)]);
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.try_remove(&4), Some(4));
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn belady_cache_bounds_lru_hits() {
+        let trace: Vec<usize> = (0..10).flat_map(|_| 0..5).collect();
+        let mut belady_cache = BeladyCache::<usize, usize, usize>::with_registry(
+            BeladyRegistry::with_trace(4, trace.iter().copied()),
+        );
+        let mut lru_cache = LruCache::<usize, usize, usize>::new(Some(4));
+        let (mut belady_hits, mut lru_hits) = (0, 0);
+
+        for &k in trace.iter() {
+            match belady_cache.get_mut(&k) {
+                Some(_) => belady_hits += 1,
+                None => _ = belady_cache.insert(k, k),
+            }
+            match lru_cache.get_mut(&k) {
+                Some(_) => lru_hits += 1,
+                None => _ = lru_cache.insert(k, k),
+            }
+        }
+
+        assert_eq!(lru_hits, 0);
+        assert!(belady_hits > 30);
+    }
+}
@@ -31,6 +31,14 @@ pub trait KeyPolicy<K> {
     fn try_remove(&mut self, key: &K) -> Option<K>;
 }
 
+/// Registries pre-allocate at most this many keys, such that unbounded ones do not overflow.
+const MAX_PREALLOCATION: usize = 1024;
+
+/// The number of keys to pre-allocate for at most `capacity` keys.
+pub(crate) fn preallocation(capacity: usize) -> usize {
+    capacity.min(MAX_PREALLOCATION)
+}
+
 // TODO: split into smaller traits
 pub trait KeyRegistry<K>: Sized {
     // type Key;
@@ -54,6 +62,12 @@ pub trait KeyRegistry<K>: Sized {
     // return deleted key (if some)
     fn add_or_update(&mut self, key: Self::KeyExtension) -> Option<K>;
 
+    /// Keys deleted by the last `add_or_update` in addition to the returned one,
+    /// e.g. to make room for a heavy key.
+    fn drain_evicted(&mut self) -> Vec<K> {
+        Vec::new()
+    }
+
     fn try_remove(&mut self, key: &K) -> Option<K>;
 }
 
@@ -92,6 +106,22 @@ where
     }
 }
 
+impl<K, KeyReg, KeyExt, V> Cache<K, KeyReg, KeyExt, V>
+where
+    K: Eq + Hash + Clone,
+    KeyReg: KeyRegistry<K, KeyExtension = KeyExt> + HouseKeeper<K>,
+{
+    /// Removes the keys which the registry invalidated, e.g. expired ones, from the store.
+    /// Returns the number of removed key-value pairs.
+    pub fn house_keeping(&mut self) -> usize {
+        let invalidated_keys = self.key_registry.house_keeping().unwrap_or_default();
+        invalidated_keys
+            .iter()
+            .filter(|k| self.store.remove(k).is_some())
+            .count()
+    }
+}
+
 impl<K, KeyReg, KeyExt, V> Cache<K, KeyReg, KeyExt, V>
 where
     K: Eq + Hash + Clone,
@@ -99,12 +129,27 @@ where
     KeyExt: KeyExtension<K>,
 {
     pub fn new(max_capacity: Option<usize>) -> Self {
+        Self::with_registry(KeyReg::init(max_capacity))
+    }
+
+    /// Creates a cache on top of an already configured key registry.
+    pub fn with_registry(key_registry: KeyReg) -> Self {
         Self {
             store: HashMap::new(),
-            key_registry: KeyReg::init(max_capacity),
+            key_registry,
         }
     }
 
+    /// The key registry, e.g. for registry specific extensions of the cache or for reading the
+    /// registry's diagnostics.
+    pub fn key_registry(&self) -> &KeyReg {
+        &self.key_registry
+    }
+
+    pub(crate) fn key_registry_mut(&mut self) -> &mut KeyReg {
+        &mut self.key_registry
+    }
+
     /// Clears the registry and store, removing all key-value pairs.
     /// Keeps the allocated memory for reuse.
     pub fn clear(&mut self) {
@@ -127,14 +172,22 @@ where
     /// If the cache did have this key present, the value is updated, and the old value is returned.
     /// TODO: remove or keep? The key is not updated, though; this matters for types that can be == without being identical.
     /// See the module-level documentation for more.
-    pub fn insert(&mut self, key: KeyExt, value: V) -> Option<V>
-    where
-        KeyExt: Clone,
-    {
-        if let Some(deleted_key) = self.key_registry.add_or_update(key.clone()) {
-            self.store.remove(&deleted_key);
+    /// If the registry rejects the key, the value is not inserted and a previous value is removed.
+    pub fn insert(&mut self, key: KeyExt, value: V) -> Option<V> {
+        let k = key.key().clone();
+        let deleted_key = self.key_registry.add_or_update(key);
+        for evicted_key in self.key_registry.drain_evicted() {
+            self.store.remove(&evicted_key);
+        }
+
+        match deleted_key {
+            Some(deleted_key) if deleted_key == k => self.store.remove(&k),
+            Some(deleted_key) => {
+                self.store.remove(&deleted_key);
+                self.store.insert(k, value)
+            }
+            None => self.store.insert(k, value),
         }
-        self.store.insert(key.key().clone(), value)
     }
 
     /// TODO: make sure keys are aligne within registry and store
@@ -0,0 +1,315 @@
+use crate::{
+    cache::{preallocation, Cache, GetKey, GetKeyMut, KeyRegistry},
+    concurrent_cache::ConcurrentCache,
+    key::KeyExtension,
+    linked_list::LinkedList,
+};
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+#[derive(Debug, Clone, Copy)]
+enum Location {
+    T1(usize),
+    T2(usize),
+    B1(usize),
+    B2(usize),
+}
+
+#[derive(Debug)]
+struct CarEntry<KeyExt> {
+    key: KeyExt,
+    /// set by hits behind a shared reference, such that concurrent reads only need a read lock
+    referenced: AtomicBool,
+}
+
+/// [`CAR`](https://www.usenix.org/legacy/publications/library/proceedings/fast04/tech/full_papers/bansal/bansal.pdf)
+/// (Clock with Adaptive Replacement) registry, combining the adaptive balancing of ARC with the
+/// cheap hits of CLOCK: a hit only sets the key's reference bit, hence reads do not need
+/// exclusive access.
+/// The clocks T1 and T2 hold keys seen once and at least twice recently, respectively;
+/// the ghost lists B1 and B2 remember keys evicted from them and adapt the target size `p` of T1.
+/// Takes O(1) for finding and removing keys, and amortized O(1) for evicting keys.
+#[derive(Debug)]
+pub struct CarRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+{
+    lookup: HashMap<K, Location>,
+    /// the clock of keys seen once recently, the hand pointing to the back
+    t1: LinkedList<CarEntry<KeyExt>>,
+    /// the clock of keys seen at least twice recently, the hand pointing to the back
+    t2: LinkedList<CarEntry<KeyExt>>,
+    /// ghost keys evicted from t1, most recently evicted in front
+    b1: LinkedList<K>,
+    /// ghost keys evicted from t2, most recently evicted in front
+    b2: LinkedList<K>,
+    /// target size of t1
+    p: usize,
+    max_capacity: usize,
+}
+
+impl<KeyExt, K> CarRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + Clone,
+{
+    /// The current target size of T1, the clock of keys seen once recently.
+    pub fn p(&self) -> usize {
+        self.p
+    }
+
+    /// Sweeps the clocks until an unreferenced key is found and moves it into its ghost list.
+    /// Referenced keys of T1 are promoted to T2, those of T2 get another round.
+    fn replace(&mut self) -> Option<K> {
+        loop {
+            if self.t1.len() >= self.p.max(1) {
+                let idx = self.t1.back()?;
+                if *self.t1.get_mut(idx).referenced.get_mut() {
+                    let mut entry = self.t1.remove(idx);
+                    *entry.referenced.get_mut() = false;
+                    let k = entry.key.key().clone();
+                    let idx = self.t2.push_front(entry);
+                    self.lookup.insert(k, Location::T2(idx));
+                } else {
+                    let k = self.t1.remove(idx).key.key().clone();
+                    let idx = self.b1.push_front(k.clone());
+                    self.lookup.insert(k.clone(), Location::B1(idx));
+                    return Some(k);
+                }
+            } else {
+                let idx = self.t2.back()?;
+                let referenced = self.t2.get_mut(idx).referenced.get_mut();
+                if *referenced {
+                    *referenced = false;
+                    self.t2.move_to_front(idx);
+                } else {
+                    let k = self.t2.remove(idx).key.key().clone();
+                    let idx = self.b2.push_front(k.clone());
+                    self.lookup.insert(k.clone(), Location::B2(idx));
+                    return Some(k);
+                }
+            }
+        }
+    }
+
+    fn forget_b1(&mut self) {
+        if let Some(k) = self.b1.pop_back() {
+            self.lookup.remove(&k);
+        }
+    }
+
+    fn forget_b2(&mut self) {
+        if let Some(k) = self.b2.pop_back() {
+            self.lookup.remove(&k);
+        }
+    }
+}
+
+impl<KeyExt, K> GetKey<K> for CarRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Eq + Hash,
+{
+    /// Takes O(1) for finding the key and setting its reference bit.
+    /// Unlike for other registries, the hit is recorded, as it does not reorder any keys.
+    fn get(&self, key: &K) -> Option<&K> {
+        let entry = match *self.lookup.get(key)? {
+            Location::T1(idx) => self.t1.get(idx),
+            Location::T2(idx) => self.t2.get(idx),
+            Location::B1(_) | Location::B2(_) => return None,
+        };
+        entry.referenced.store(true, Ordering::Relaxed);
+        Some(entry.key.key())
+    }
+}
+
+impl<KeyExt, K> GetKeyMut<K> for CarRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Eq + Hash,
+{
+    /// Takes O(1) for finding the key and setting its reference bit.
+    fn get(&mut self, key: &K) -> Option<&K> {
+        GetKey::get(self, key)
+    }
+}
+
+impl<KeyExt, K> KeyRegistry<K> for CarRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + PartialEq + Clone,
+{
+    type KeyExtension = KeyExt;
+
+    fn with_capacity(max_capacity: usize) -> Self {
+        Self {
+            lookup: HashMap::with_capacity(preallocation(max_capacity)),
+            t1: LinkedList::with_capacity(preallocation(max_capacity)),
+            t2: LinkedList::with_capacity(preallocation(max_capacity)),
+            b1: LinkedList::with_capacity(preallocation(max_capacity)),
+            b2: LinkedList::with_capacity(preallocation(max_capacity)),
+            p: 0,
+            max_capacity,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.lookup.clear();
+        self.t1.clear();
+        self.t2.clear();
+        self.b1.clear();
+        self.b2.clear();
+        self.p = 0;
+    }
+
+    fn len(&self) -> usize {
+        self.t1.len() + self.t2.len()
+    }
+
+    /// Takes amortized O(1); an already present key is updated in place and referenced as on a hit.
+    fn add_or_update(&mut self, key: Self::KeyExtension) -> Option<K> {
+        let location = self.lookup.get(key.key()).copied();
+        let entry = match location {
+            Some(Location::T1(idx)) => Some(self.t1.get_mut(idx)),
+            Some(Location::T2(idx)) => Some(self.t2.get_mut(idx)),
+            _ => None,
+        };
+        if let Some(entry) = entry {
+            entry.key = key;
+            *entry.referenced.get_mut() = true;
+            return None;
+        }
+
+        let deleted_key = if self.len() >= self.max_capacity {
+            let deleted_key = self.replace();
+            if location.is_none() {
+                if self.t1.len() + self.b1.len() >= self.max_capacity {
+                    self.forget_b1();
+                } else if self.len() + self.b1.len() + self.b2.len()
+                    >= self.max_capacity.saturating_mul(2)
+                {
+                    self.forget_b2();
+                }
+            }
+            deleted_key
+        } else {
+            None
+        };
+
+        let entry = CarEntry {
+            key,
+            referenced: AtomicBool::new(false),
+        };
+        let k = entry.key.key().clone();
+        match location {
+            Some(Location::B1(idx)) => {
+                let delta = (self.b2.len() / self.b1.len()).max(1);
+                self.p = self.p.saturating_add(delta).min(self.max_capacity);
+                self.b1.remove(idx);
+                let idx = self.t2.push_front(entry);
+                self.lookup.insert(k, Location::T2(idx));
+            }
+            Some(Location::B2(idx)) => {
+                let delta = (self.b1.len() / self.b2.len()).max(1);
+                self.p = self.p.saturating_sub(delta);
+                self.b2.remove(idx);
+                let idx = self.t2.push_front(entry);
+                self.lookup.insert(k, Location::T2(idx));
+            }
+            _ => {
+                let idx = self.t1.push_front(entry);
+                self.lookup.insert(k, Location::T1(idx));
+            }
+        }
+        deleted_key
+    }
+
+    /// Takes O(1); ghost keys are not affected.
+    fn try_remove(&mut self, key: &K) -> Option<K> {
+        let entry = match self.lookup.get(key).copied()? {
+            Location::T1(idx) => self.t1.remove(idx),
+            Location::T2(idx) => self.t2.remove(idx),
+            Location::B1(_) | Location::B2(_) => return None,
+        };
+        self.lookup.remove(key);
+        Some(entry.key.key().clone())
+    }
+}
+
+pub type CarCache<K, KeyExt, V> = Cache<K, CarRegistry<KeyExt, K>, KeyExt, V>;
+pub type ConcurrentCarCache<K, KeyExt, V> = ConcurrentCache<K, CarRegistry<KeyExt, K>, KeyExt, V>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn car_cache_init() {
+        let mut cache = CarCache::<i32, i32, String>::new(Some(4));
+        cache.insert(1, "How".to_string());
+        cache.insert(2, "Hi".to_string());
+        cache.insert(3, "Are".to_string());
+        cache.insert(4, "You".to_string());
+        cache.insert(5, "Doing".to_string());
+        cache.insert(2, "How".to_string());
+
+        assert_eq!(cache.len(), 4);
+
+        assert_eq!(cache.get_mut(&1), None);
+        assert_eq!(cache.get_mut(&2).cloned(), Some("How".to_string()));
+        assert_eq!(cache.get_mut(&3).cloned(), Some("Are".to_string()));
+        assert_eq!(cache.get_mut(&4).cloned(), Some("You".to_string()));
+        assert_eq!(cache.get_mut(&5).cloned(), Some("Doing".to_string()));
+        assert_eq!(cache.key_registry().p(), 0);
+    }
+
+    #[test]
+    fn car_registry_adapts_on_ghost_hits() {
+        let mut registry = CarRegistry::<i32, i32>::with_capacity(2);
+        registry.add_or_update(1);
+        registry.get(&1);
+        registry.add_or_update(2);
+
+        // the referenced 1 is promoted to t2, while 2 is moved to b1
+        assert_eq!(registry.add_or_update(3), Some(2));
+        assert_eq!(registry.p(), 0);
+
+        // ghost hit in b1 grows t1's target, 3 is moved to b1
+        assert_eq!(registry.add_or_update(2), Some(3));
+        assert_eq!(registry.p(), 1);
+
+        // t1 is empty, so 1 is moved from t2 to b2
+        assert_eq!(registry.add_or_update(4), Some(1));
+
+        // ghost hit in b2 shrinks t1's target
+        assert_eq!(registry.add_or_update(1), Some(4));
+        assert_eq!(registry.p(), 0);
+
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.try_remove(&4), None);
+        assert_eq!(registry.try_remove(&1), Some(1));
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn concurrent_car_cache_references_keys_on_shared_reads() {
+        let cache = ConcurrentCarCache::<i32, i32, String>::new(Some(2));
+        cache.insert(1, "How".to_string());
+        cache.insert(2, "Are".to_string());
+
+        std::thread::scope(|scope| {
+            for _ in 0..2 {
+                scope.spawn(|| assert_eq!(cache.get(&1).as_deref(), Some(&"How".to_string())));
+            }
+        });
+
+        // the referenced 1 is promoted to t2, while 2 is moved to b1
+        cache.insert(3, "You".to_string());
+        assert_eq!(cache.get(&2), None);
+        assert_eq!(cache.get(&1).as_deref(), Some(&"How".to_string()));
+        assert_eq!(cache.len(), 2);
+    }
+}
@@ -0,0 +1,209 @@
+use crate::{
+    cache::{preallocation, Cache, GetKeyMut, KeyRegistry},
+    concurrent_cache::ConcurrentCache,
+    key::KeyExtension,
+};
+use std::{collections::HashMap, hash::Hash};
+
+#[derive(Debug)]
+struct ClockEntry<KeyExt> {
+    key: KeyExt,
+    referenced: bool,
+}
+
+/// [`CLOCK`](https://en.wikipedia.org/wiki/Page_replacement_algorithm#Clock) registry,
+/// also known as second chance.
+/// Keys are kept in a circular buffer and a hit only sets the key's reference bit.
+/// On eviction the hand sweeps the buffer, clearing the bits, until it finds an unreferenced key.
+/// Takes O(1) for finding and removing keys, and amortized O(1) for evicting keys.
+#[derive(Debug)]
+pub struct ClockRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+{
+    idx_lookup: HashMap<K, usize>,
+    /// the circular buffer, growing up to `max_capacity`
+    entries: Vec<Option<ClockEntry<KeyExt>>>,
+    /// slots of removed keys which can be reused
+    free: Vec<usize>,
+    hand: usize,
+    max_capacity: usize,
+}
+
+impl<KeyExt, K> ClockRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Eq + Hash,
+{
+    /// Sweeps the buffer until an unreferenced key is found and frees its slot.
+    fn evict(&mut self) -> Option<KeyExt> {
+        if self.idx_lookup.is_empty() {
+            return None;
+        }
+
+        loop {
+            if self.hand >= self.entries.len() {
+                self.hand = 0;
+            }
+            match &mut self.entries[self.hand] {
+                Some(entry) if entry.referenced => entry.referenced = false,
+                Some(_) => {
+                    let entry = self.entries[self.hand].take()?;
+                    self.idx_lookup.remove(entry.key.key());
+                    self.free.push(self.hand);
+                    self.hand += 1;
+                    return Some(entry.key);
+                }
+                None => {}
+            }
+            self.hand += 1;
+        }
+    }
+}
+
+impl<KeyExt, K> GetKeyMut<K> for ClockRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Eq + Hash,
+{
+    /// Takes O(1) for finding the key and setting its reference bit.
+    fn get(&mut self, key: &K) -> Option<&K> {
+        let idx = *self.idx_lookup.get(key)?;
+        self.entries[idx].as_mut().map(|entry| {
+            entry.referenced = true;
+            entry.key.key()
+        })
+    }
+}
+
+impl<KeyExt, K> KeyRegistry<K> for ClockRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + PartialEq + Clone,
+{
+    type KeyExtension = KeyExt;
+
+    fn with_capacity(max_capacity: usize) -> Self {
+        Self {
+            idx_lookup: HashMap::with_capacity(preallocation(max_capacity)),
+            entries: Vec::with_capacity(preallocation(max_capacity)),
+            free: Vec::new(),
+            hand: 0,
+            max_capacity,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.idx_lookup.clear();
+        self.entries.clear();
+        self.free.clear();
+        self.hand = 0;
+    }
+
+    fn len(&self) -> usize {
+        self.idx_lookup.len()
+    }
+
+    /// Takes O(1) if there is space left; an already present key is updated in place.
+    fn add_or_update(&mut self, key: Self::KeyExtension) -> Option<K> {
+        if let Some(&idx) = self.idx_lookup.get(key.key()) {
+            if let Some(entry) = self.entries[idx].as_mut() {
+                entry.key = key;
+            }
+            return None;
+        }
+
+        let deleted_key = if self.len() >= self.max_capacity {
+            self.evict().map(|tk| tk.key().clone())
+        } else {
+            None
+        };
+
+        let k = key.key().clone();
+        let entry = Some(ClockEntry {
+            key,
+            referenced: false,
+        });
+        let idx = match self.free.pop() {
+            Some(idx) => {
+                self.entries[idx] = entry;
+                idx
+            }
+            None => {
+                self.entries.push(entry);
+                self.entries.len() - 1
+            }
+        };
+        self.idx_lookup.insert(k, idx);
+        deleted_key
+    }
+
+    /// Takes O(1).
+    fn try_remove(&mut self, key: &K) -> Option<K> {
+        let idx = self.idx_lookup.remove(key)?;
+        self.free.push(idx);
+        self.entries[idx]
+            .take()
+            .map(|entry| entry.key.key().clone())
+    }
+}
+
+pub type ClockCache<K, KeyExt, V> = Cache<K, ClockRegistry<KeyExt, K>, KeyExt, V>;
+pub type ConcurrentClockCache<K, KeyExt, V> =
+    ConcurrentCache<K, ClockRegistry<KeyExt, K>, KeyExt, V>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clock_cache_init() {
+        let mut cache = ClockCache::<i32, i32, String>::new(Some(4));
+        cache.insert(1, "How".to_string());
+        cache.insert(2, "Hi".to_string());
+        cache.insert(3, "Are".to_string());
+        cache.insert(4, "You".to_string());
+        cache.insert(5, "Doing".to_string());
+        cache.insert(2, "How".to_string());
+
+        assert_eq!(cache.len(), 4);
+
+        assert_eq!(cache.get_mut(&1), None);
+        assert_eq!(cache.get_mut(&2).cloned(), Some("How".to_string()));
+        assert_eq!(cache.get_mut(&3).cloned(), Some("Are".to_string()));
+        assert_eq!(cache.get_mut(&4).cloned(), Some("You".to_string()));
+        assert_eq!(cache.get_mut(&5).cloned(), Some("Doing".to_string()));
+    }
+
+    #[test]
+    fn clock_registry_gives_second_chance() {
+        let mut registry = ClockRegistry::<i32, i32>::with_capacity(3);
+        registry.add_or_update(1);
+        registry.add_or_update(2);
+        registry.add_or_update(3);
+
+        registry.get(&1);
+        assert_eq!(registry.add_or_update(4), Some(2));
+        assert_eq!(registry.add_or_update(5), Some(3));
+        // the reference bit of 1 was cleared by the first sweep
+        assert_eq!(registry.add_or_update(6), Some(1));
+
+        assert_eq!(registry.try_remove(&5), Some(5));
+        assert_eq!(registry.add_or_update(7), None);
+        assert_eq!(registry.len(), 3);
+    }
+
+    #[test]
+    fn concurrent_clock_cache_init() {
+        let mut cache = ConcurrentClockCache::<i32, i32, String>::new(Some(2));
+        cache.insert(1, "How".to_string());
+        cache.insert(2, "Are".to_string());
+        assert_eq!(cache.get_mut(&1).as_deref(), Some(&"How".to_string()));
+        cache.insert(3, "You".to_string());
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get_mut(&2), None);
+        assert_eq!(cache.get_mut(&1).as_deref(), Some(&"How".to_string()));
+        assert_eq!(cache.get_mut(&3).as_deref(), Some(&"You".to_string()));
+    }
+}
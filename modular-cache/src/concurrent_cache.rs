@@ -1,10 +1,10 @@
 use crate::{
-    cache::{Cache, GetKey, GetKeyMut, KeyRegistry},
+    cache::{Cache, GetKey, GetKeyMut, HouseKeeper, KeyRegistry},
     key::KeyExtension,
 };
 use std::{
     hash::Hash,
-    sync::{Arc, RwLock},
+    sync::{Arc, RwLock, RwLockWriteGuard},
 };
 
 // TODO: use different concurrent primitives via features
@@ -44,6 +44,19 @@ where
     }
 }
 
+impl<K, KeyReg, KeyExt, V> ConcurrentCache<K, KeyReg, KeyExt, V>
+where
+    K: Eq + Hash + Clone,
+    KeyReg: KeyRegistry<K, KeyExtension = KeyExt> + HouseKeeper<K>,
+{
+    /// Removes the keys which the registry invalidated, e.g. expired ones.
+    /// Locks this cache, blocking the current thread until it can be acquired.
+    pub fn house_keeping(&self) -> usize {
+        let mut guard = self.inner.write().unwrap();
+        guard.house_keeping()
+    }
+}
+
 impl<K, KeyReg, KeyExt, V> ConcurrentCache<K, KeyReg, KeyExt, V>
 where
     K: Eq + Hash + Clone,
@@ -56,6 +69,18 @@ where
         }
     }
 
+    /// Creates a cache on top of an already configured key registry.
+    pub fn with_registry(key_registry: KeyReg) -> Self {
+        Self {
+            inner: RwLock::new(Cache::with_registry(key_registry)),
+        }
+    }
+
+    /// Locks this cache for writing, e.g. for registry specific extensions of the cache.
+    pub(crate) fn write(&self) -> RwLockWriteGuard<'_, Cache<K, KeyReg, KeyExt, Arc<V>>> {
+        self.inner.write().unwrap()
+    }
+
     pub fn len(&self) -> usize {
         let guard = self.inner.read().unwrap();
         guard.len()
@@ -0,0 +1,183 @@
+use crate::{
+    cache::preallocation,
+    key::KeyExtension,
+    linked_list::LinkedList,
+    timing_wheel::{TimerHandle, TimingWheel},
+};
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    time::{Duration, Instant},
+};
+
+/// Granularity of the deadlines; expired keys are purged up to one tick after their deadline.
+const TICK: Duration = Duration::from_millis(1);
+
+/// Where the key waits for its expiration.
+#[derive(Debug, Clone, Copy)]
+enum Expiration {
+    /// the timer firing at the deadline
+    Scheduled(TimerHandle),
+    /// position in the list of keys which never expire
+    Never(usize),
+}
+
+#[derive(Debug)]
+struct DeadlineEntry<KeyExt> {
+    key: KeyExt,
+    deadline: Option<Instant>,
+    expiration: Expiration,
+}
+
+/// Keys ordered by their deadline, the core of the time-based registries, which only differ in
+/// how they derive the deadlines. Expired keys are treated as absent and purged on the next
+/// `add_or_update` or housekeeping pass. If the queue is full, the key expiring first is evicted,
+/// or the earliest added one if no key expires.
+/// Deadlines are kept in a [`TimingWheel`], such that it takes O(1) for finding, adding,
+/// rescheduling and removing keys, O(1) per purged key and processed slot of the wheel for
+/// purging, and O(1) per key sharing the slot of the key expiring first for evicting it.
+#[derive(Debug)]
+pub(crate) struct DeadlineQueue<KeyExt, K> {
+    entries: HashMap<K, DeadlineEntry<KeyExt>>,
+    /// keys which expire, scheduled at their deadline
+    wheel: TimingWheel<K>,
+    /// keys which never expire, ordered by insertion, i.e. latest in front, earliest in back
+    eternal: LinkedList<K>,
+    /// keys purged by the last `add_or_update` in addition to the returned one
+    evicted: Vec<K>,
+    max_capacity: usize,
+}
+
+impl<KeyExt, K> DeadlineQueue<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + Clone,
+{
+    pub(crate) fn with_capacity(max_capacity: usize) -> Self {
+        Self {
+            entries: HashMap::with_capacity(preallocation(max_capacity)),
+            wheel: TimingWheel::new(Instant::now(), TICK),
+            eternal: LinkedList::with_capacity(0),
+            evicted: Vec::new(),
+            max_capacity,
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+        self.wheel.clear();
+        self.eternal.clear();
+        self.evicted.clear();
+    }
+
+    /// The number of keys, including expired ones which are not purged yet.
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// The key, unless it is absent or expired.
+    pub(crate) fn get(&self, key: &K) -> Option<&K> {
+        let entry = self.entries.get(key)?;
+        match entry.deadline {
+            Some(deadline) if deadline <= Instant::now() => None,
+            _ => Some(entry.key.key()),
+        }
+    }
+
+    /// The instant the key expires at, or `None` if it never expires or is not present.
+    pub(crate) fn deadline(&self, key: &K) -> Option<Instant> {
+        self.entries.get(key).and_then(|entry| entry.deadline)
+    }
+
+    /// Changes the deadline of a present key. Returns whether the key was present.
+    pub(crate) fn set_deadline(&mut self, key: &K, deadline: Option<Instant>) -> bool {
+        let Some(expiration) = self.entries.get(key).map(|entry| entry.expiration) else {
+            return false;
+        };
+        self.unschedule(expiration);
+        let expiration = self.schedule(key.clone(), deadline);
+        if let Some(entry) = self.entries.get_mut(key) {
+            entry.deadline = deadline;
+            entry.expiration = expiration;
+        }
+        true
+    }
+
+    /// Adds the key, or replaces it if present, expiring at the `deadline`. Returns the key
+    /// expiring first if the queue is full, or else one of the purged keys; the remaining purged
+    /// keys are returned by `drain_evicted`.
+    pub(crate) fn add_or_update(&mut self, key: KeyExt, deadline: Option<Instant>) -> Option<K> {
+        let k = key.key().clone();
+        self.evicted = self.purge();
+        // an expired value of the key itself is replaced rather than deleted
+        self.evicted.retain(|purged| purged != &k);
+        let present = self.try_remove(&k).is_some();
+
+        let deleted_key = if !present && self.len() >= self.max_capacity {
+            let earliest = self.wheel.pop_earliest();
+            let deleted_key = earliest.or_else(|| self.eternal.pop_back());
+            if let Some(k) = &deleted_key {
+                self.entries.remove(k);
+            }
+            deleted_key
+        } else {
+            self.evicted.pop()
+        };
+
+        let expiration = self.schedule(k.clone(), deadline);
+        self.entries.insert(
+            k,
+            DeadlineEntry {
+                key,
+                deadline,
+                expiration,
+            },
+        );
+        deleted_key
+    }
+
+    /// Keys purged by the last `add_or_update` in addition to the returned one.
+    pub(crate) fn drain_evicted(&mut self) -> Vec<K> {
+        std::mem::take(&mut self.evicted)
+    }
+
+    /// Removes the key, even if it expired.
+    pub(crate) fn try_remove(&mut self, key: &K) -> Option<K> {
+        let entry = self.entries.remove(key)?;
+        self.unschedule(entry.expiration);
+        Some(entry.key.key().clone())
+    }
+
+    /// Purges all expired keys.
+    pub(crate) fn house_keeping(&mut self) -> Option<HashSet<K>> {
+        let purged = self.purge();
+        (!purged.is_empty()).then(|| purged.into_iter().collect())
+    }
+
+    /// Removes all expired keys and returns them.
+    fn purge(&mut self) -> Vec<K> {
+        let purged = self.wheel.advance(Instant::now());
+        for k in &purged {
+            self.entries.remove(k);
+        }
+        purged
+    }
+
+    fn schedule(&mut self, key: K, deadline: Option<Instant>) -> Expiration {
+        match deadline {
+            Some(deadline) => Expiration::Scheduled(self.wheel.schedule(deadline, key)),
+            None => Expiration::Never(self.eternal.push_front(key)),
+        }
+    }
+
+    fn unschedule(&mut self, expiration: Expiration) {
+        match expiration {
+            Expiration::Scheduled(handle) => {
+                self.wheel.cancel(handle);
+            }
+            Expiration::Never(idx) => {
+                self.eternal.remove(idx);
+            }
+        }
+    }
+}
@@ -0,0 +1,350 @@
+use crate::{
+    cache::{Cache, GetKey, HouseKeeper, KeyRegistry},
+    concurrent_cache::ConcurrentCache,
+    deadline_queue::DeadlineQueue,
+    key::{Expiring, ExpiringKey, KeyExtension},
+};
+use std::{
+    collections::HashSet,
+    hash::Hash,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// Computes the time-to-live of an entry from its key and value.
+/// A `None` time-to-live never expires; `remaining` is the time-to-live left before the event.
+pub trait Expiry<K, V> {
+    fn expire_after_create(&self, key: &K, value: &V) -> Option<Duration>;
+
+    /// Keeps the remaining time-to-live by default.
+    fn expire_after_update(
+        &self,
+        _key: &K,
+        _value: &V,
+        remaining: Option<Duration>,
+    ) -> Option<Duration> {
+        remaining
+    }
+
+    /// Keeps the remaining time-to-live by default.
+    fn expire_after_read(
+        &self,
+        _key: &K,
+        _value: &V,
+        remaining: Option<Duration>,
+    ) -> Option<Duration> {
+        remaining
+    }
+}
+
+/// Registry expiring each key at its own deadline, as given by its [`Expiring`] extension or
+/// changed by `set_deadline`. Expired keys are treated as absent and purged on the next
+/// `add_or_update` or housekeeping pass. If the registry is full, the key expiring first is
+/// evicted, or the earliest added one if no key expires.
+/// Deadlines are kept in a [`TimingWheel`](crate::timing_wheel::TimingWheel), such that it takes
+/// O(1) for finding, adding, rescheduling and removing keys, O(1) per purged key and processed
+/// slot of the wheel for purging, and O(1) per key sharing the slot of the key expiring first for
+/// evicting it.
+#[derive(Debug)]
+pub struct ExpiringRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+{
+    queue: DeadlineQueue<KeyExt, K>,
+}
+
+impl<KeyExt, K> ExpiringRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K> + Expiring,
+    K: Hash + Eq + Clone,
+{
+    /// The instant the key expires at, or `None` if it never expires or is not present.
+    pub fn deadline(&self, key: &K) -> Option<Instant> {
+        self.queue.deadline(key)
+    }
+
+    /// Changes the deadline of a present key without adding it again.
+    /// Returns whether the key was present.
+    pub fn set_deadline(&mut self, key: &K, deadline: Option<Instant>) -> bool {
+        self.queue.set_deadline(key, deadline)
+    }
+}
+
+impl<KeyExt, K> GetKey<K> for ExpiringRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K> + Expiring,
+    K: Hash + Eq + Clone,
+{
+    /// Takes O(1) for finding the key; expired keys are not found.
+    fn get(&self, key: &K) -> Option<&K> {
+        self.queue.get(key)
+    }
+}
+
+impl<KeyExt, K> HouseKeeper<K> for ExpiringRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K> + Expiring,
+    K: Hash + Eq + Clone,
+{
+    /// Purges all expired keys.
+    fn house_keeping(&mut self) -> Option<HashSet<K>> {
+        self.queue.house_keeping()
+    }
+}
+
+impl<KeyExt, K> KeyRegistry<K> for ExpiringRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K> + Expiring,
+    K: Hash + Eq + PartialEq + Clone,
+{
+    type KeyExtension = KeyExt;
+
+    fn with_capacity(max_capacity: usize) -> Self {
+        Self {
+            queue: DeadlineQueue::with_capacity(max_capacity),
+        }
+    }
+
+    fn clear(&mut self) {
+        self.queue.clear();
+    }
+
+    /// The number of keys, including expired ones which are not purged yet.
+    fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Takes O(1) per purged key; an already present key takes the new deadline.
+    fn add_or_update(&mut self, key: Self::KeyExtension) -> Option<K> {
+        let deadline = key.deadline();
+        self.queue.add_or_update(key, deadline)
+    }
+
+    fn drain_evicted(&mut self) -> Vec<K> {
+        self.queue.drain_evicted()
+    }
+
+    /// Takes O(1); expired keys are removed as well.
+    fn try_remove(&mut self, key: &K) -> Option<K> {
+        self.queue.try_remove(key)
+    }
+}
+
+pub type ExpiringCache<K, KeyExt, V> = Cache<K, ExpiringRegistry<KeyExt, K>, KeyExt, V>;
+pub type ConcurrentExpiringCache<K, KeyExt, V> =
+    ConcurrentCache<K, ExpiringRegistry<KeyExt, K>, KeyExt, V>;
+
+fn remaining(deadline: Option<Instant>, now: Instant) -> Option<Duration> {
+    deadline.map(|deadline| deadline.saturating_duration_since(now))
+}
+
+impl<K, V> Cache<K, ExpiringRegistry<ExpiringKey<K>, K>, ExpiringKey<K>, V>
+where
+    K: Hash + Eq + Clone,
+{
+    /// Inserts a key-value pair which expires as computed by the `expiry`,
+    /// on creation if the key is not present yet and on update otherwise.
+    pub fn insert_with_expiry<E>(&mut self, key: K, value: V, expiry: &E) -> Option<V>
+    where
+        E: Expiry<K, V>,
+    {
+        let now = Instant::now();
+        let ttl = match self.key_registry().get(&key) {
+            Some(_) => {
+                let deadline = self.key_registry().deadline(&key);
+                expiry.expire_after_update(&key, &value, remaining(deadline, now))
+            }
+            None => expiry.expire_after_create(&key, &value),
+        };
+        let key = match ttl.and_then(|ttl| now.checked_add(ttl)) {
+            Some(deadline) => ExpiringKey::new(key, deadline),
+            None => ExpiringKey::never_expiring(key),
+        };
+        self.insert(key, value)
+    }
+
+    /// Gets the key's value and updates its deadline as computed by the `expiry` on read.
+    /// Returns `None` if the key expires right away.
+    pub fn get_with_expiry<E>(&mut self, key: &K, expiry: &E) -> Option<&V>
+    where
+        E: Expiry<K, V>,
+    {
+        let now = Instant::now();
+        let deadline = self.key_registry().deadline(key);
+        let ttl = expiry.expire_after_read(key, self.get(key)?, remaining(deadline, now));
+        let deadline = ttl.and_then(|ttl| now.checked_add(ttl));
+        self.key_registry_mut().set_deadline(key, deadline);
+        self.get(key)
+    }
+}
+
+/// Computes the time-to-live of the shared values of a concurrent cache by the `Expiry` of the
+/// values themselves.
+struct SharedExpiry<'a, E>(&'a E);
+
+impl<K, V, E> Expiry<K, Arc<V>> for SharedExpiry<'_, E>
+where
+    E: Expiry<K, V>,
+{
+    fn expire_after_create(&self, key: &K, value: &Arc<V>) -> Option<Duration> {
+        self.0.expire_after_create(key, value)
+    }
+
+    fn expire_after_update(
+        &self,
+        key: &K,
+        value: &Arc<V>,
+        remaining: Option<Duration>,
+    ) -> Option<Duration> {
+        self.0.expire_after_update(key, value, remaining)
+    }
+
+    fn expire_after_read(
+        &self,
+        key: &K,
+        value: &Arc<V>,
+        remaining: Option<Duration>,
+    ) -> Option<Duration> {
+        self.0.expire_after_read(key, value, remaining)
+    }
+}
+
+impl<K, V> ConcurrentCache<K, ExpiringRegistry<ExpiringKey<K>, K>, ExpiringKey<K>, V>
+where
+    K: Hash + Eq + Clone,
+{
+    /// Inserts a key-value pair which expires as computed by the `expiry`,
+    /// on creation if the key is not present yet and on update otherwise.
+    /// Locks this cache, blocking the current thread until it can be acquired.
+    pub fn insert_with_expiry<E>(&self, key: K, value: V, expiry: &E) -> Option<Arc<V>>
+    where
+        E: Expiry<K, V>,
+    {
+        let mut guard = self.write();
+        guard.insert_with_expiry(key, Arc::new(value), &SharedExpiry(expiry))
+    }
+
+    /// Gets the key's value and updates its deadline as computed by the `expiry` on read.
+    /// Returns `None` if the key expires right away.
+    /// Locks this cache, blocking the current thread until it can be acquired.
+    pub fn get_with_expiry<E>(&self, key: &K, expiry: &E) -> Option<Arc<V>>
+    where
+        E: Expiry<K, V>,
+    {
+        let mut guard = self.write();
+        guard.get_with_expiry(key, &SharedExpiry(expiry)).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::ago;
+
+    #[test]
+    fn expiring_cache_expires_keys_at_own_deadline() {
+        let mut cache = ExpiringCache::<i32, ExpiringKey<i32>, String>::new(Some(4));
+        cache.insert(
+            ExpiringKey::expires_in(1, Duration::from_secs(10)),
+            "How".to_string(),
+        );
+        cache.insert(ExpiringKey::never_expiring(2), "Are".to_string());
+        let expired = ago(Duration::from_secs(1));
+        cache.insert(ExpiringKey::new(3, expired), "You".to_string());
+
+        assert_eq!(cache.get(&1).cloned(), Some("How".to_string()));
+        assert_eq!(cache.get(&2).cloned(), Some("Are".to_string()));
+        assert_eq!(cache.get(&3), None);
+        assert_eq!(cache.house_keeping(), 1);
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn expiring_cache_evicts_and_purges_by_timing_wheel() {
+        let mut cache = ExpiringCache::<i32, ExpiringKey<i32>, String>::new(Some(3));
+        let expiring = |key, secs| ExpiringKey::expires_in(key, Duration::from_secs(secs));
+        cache.insert(expiring(1, 3_600), "How".to_string());
+        cache.insert(ExpiringKey::never_expiring(2), "Are".to_string());
+        cache.insert(expiring(3, 10), "You".to_string());
+
+        // the key expiring first is evicted rather than the one which never expires
+        cache.insert(expiring(4, 60), "Doing".to_string());
+        assert_eq!(cache.get(&3), None);
+        cache.insert(ExpiringKey::never_expiring(5), "Today".to_string());
+        assert_eq!(cache.get(&4), None);
+        let expired = ago(Duration::from_secs(1));
+        cache.insert(ExpiringKey::new(6, expired), "Hi".to_string());
+        assert_eq!(cache.get(&1), None);
+
+        // the overdue timer fires on the next advance of the wheel
+        assert_eq!(cache.get(&6), None);
+        assert_eq!(cache.house_keeping(), 1);
+
+        // keys which never expire are evicted in insertion order
+        cache.insert(ExpiringKey::never_expiring(7), "There".to_string());
+        cache.insert(ExpiringKey::never_expiring(8), "There".to_string());
+        assert_eq!(cache.get(&2), None);
+        assert_eq!(cache.get(&5).cloned(), Some("Today".to_string()));
+        assert_eq!(cache.len(), 3);
+    }
+
+    /// Expires entries after as many seconds as their value.
+    struct ValueSecs;
+
+    impl Expiry<i32, u64> for ValueSecs {
+        fn expire_after_create(&self, _key: &i32, value: &u64) -> Option<Duration> {
+            Some(Duration::from_secs(*value))
+        }
+
+        fn expire_after_read(
+            &self,
+            _key: &i32,
+            value: &u64,
+            _remaining: Option<Duration>,
+        ) -> Option<Duration> {
+            (*value > 100).then_some(Duration::from_secs(1))
+        }
+    }
+
+    #[test]
+    fn expiring_cache_computes_deadlines_by_expiry() {
+        let mut cache = ExpiringCache::<i32, ExpiringKey<i32>, u64>::new(Some(4));
+        let now = Instant::now();
+        cache.insert_with_expiry(1, 0, &ValueSecs);
+        cache.insert_with_expiry(2, 10, &ValueSecs);
+        assert_eq!(cache.get_with_expiry(&1, &ValueSecs), None);
+
+        // updates keep the remaining time-to-live
+        cache.insert_with_expiry(2, 1_000, &ValueSecs);
+        let deadline = cache.key_registry().deadline(&2);
+        assert!(deadline.is_some_and(|d| d <= now + Duration::from_secs(20)));
+
+        // large values expire soon after being read
+        assert_eq!(cache.get_with_expiry(&2, &ValueSecs), Some(&1_000));
+        let deadline = cache.key_registry().deadline(&2);
+        assert!(deadline.is_some_and(|d| d <= Instant::now() + Duration::from_secs(1)));
+
+        // small values never expire once read
+        cache.insert_with_expiry(3, 10, &ValueSecs);
+        assert_eq!(cache.get_with_expiry(&3, &ValueSecs), Some(&10));
+        assert_eq!(cache.key_registry().deadline(&3), None);
+    }
+
+    #[test]
+    fn concurrent_expiring_cache_computes_deadlines_by_expiry() {
+        let cache = ConcurrentExpiringCache::<i32, ExpiringKey<i32>, u64>::new(Some(4));
+        cache.insert_with_expiry(1, 0, &ValueSecs);
+        cache.insert_with_expiry(2, 10, &ValueSecs);
+        assert_eq!(cache.get_with_expiry(&1, &ValueSecs), None);
+        assert_eq!(cache.get_with_expiry(&2, &ValueSecs).as_deref(), Some(&10));
+
+        // an expired key is created again
+        cache.insert_with_expiry(1, 1_000, &ValueSecs);
+        assert_eq!(
+            cache.get_with_expiry(&1, &ValueSecs).as_deref(),
+            Some(&1_000)
+        );
+        assert_eq!(cache.get(&2).as_deref(), Some(&10));
+        assert_eq!(cache.len(), 2);
+    }
+}
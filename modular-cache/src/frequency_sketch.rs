@@ -0,0 +1,143 @@
+use crate::cache::preallocation;
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{BuildHasher, BuildHasherDefault, Hash},
+};
+
+const DEPTH: usize = 4;
+const SEEDS: [u64; DEPTH] = [
+    0x9E37_79B9_7F4A_7C15,
+    0xC2B2_AE3D_27D4_EB4F,
+    0x1656_67B1_9E37_79F9,
+    0x85EB_CA77_C2B2_AE63,
+];
+/// Saturation of the counters, as for the 4-bit counters of the TinyLFU paper.
+const MAX_COUNT: u8 = 15;
+/// The sketch width is bounded, also for unbounded registries.
+const MAX_WIDTH: usize = 1 << 20;
+const MIN_WIDTH: usize = 16;
+
+/// [`Count-min sketch`](https://en.wikipedia.org/wiki/Count%E2%80%93min_sketch) estimating
+/// the access frequency of keys. All counters are halved after a sample of `10 * width`
+/// increments, such that the estimates age over time.
+/// Keys are hashed deterministically, hence the estimates are reproducible.
+/// The sketch starts at the pre-allocated width and grows with the number of keys up to the
+/// width for the registry's capacity.
+#[derive(Debug)]
+pub(crate) struct CountMinSketch {
+    /// `DEPTH` rows of `width` counters each
+    counters: Vec<u8>,
+    width: usize,
+    max_width: usize,
+    additions: usize,
+    sample_size: usize,
+    hasher: BuildHasherDefault<DefaultHasher>,
+}
+
+impl CountMinSketch {
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        let width = preallocation(capacity).max(MIN_WIDTH).next_power_of_two();
+        Self {
+            counters: vec![0; DEPTH * width],
+            width,
+            max_width: capacity.clamp(MIN_WIDTH, MAX_WIDTH).next_power_of_two(),
+            additions: 0,
+            sample_size: 10 * width,
+            hasher: BuildHasherDefault::default(),
+        }
+    }
+
+    /// Widens the sketch such that it fits `len` keys. Takes amortized O(1), as the width is
+    /// doubled; the counters are reset, since the keys cannot be rehashed.
+    pub(crate) fn ensure_capacity(&mut self, len: usize) {
+        if len <= self.width || self.width >= self.max_width {
+            return;
+        }
+        self.width = len.min(self.max_width).next_power_of_two();
+        self.counters = vec![0; DEPTH * self.width];
+        self.additions = 0;
+        self.sample_size = 10 * self.width;
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.counters.iter_mut().for_each(|c| *c = 0);
+        self.additions = 0;
+    }
+
+    /// The estimated frequency of the key, never below its actual (aged) frequency.
+    pub(crate) fn frequency<K: Hash>(&self, key: &K) -> u8 {
+        let hash = self.hasher.hash_one(key);
+        (0..DEPTH)
+            .map(|row| self.counters[self.index(hash, row)])
+            .min()
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn increment<K: Hash>(&mut self, key: &K) {
+        let hash = self.hasher.hash_one(key);
+        for row in 0..DEPTH {
+            let idx = self.index(hash, row);
+            self.counters[idx] = (self.counters[idx] + 1).min(MAX_COUNT);
+        }
+
+        self.additions += 1;
+        if self.additions >= self.sample_size {
+            self.age();
+        }
+    }
+
+    fn age(&mut self) {
+        self.counters.iter_mut().for_each(|c| *c /= 2);
+        self.additions /= 2;
+    }
+
+    fn index(&self, hash: u64, row: usize) -> usize {
+        let h = hash.wrapping_add(SEEDS[row]).wrapping_mul(SEEDS[row]);
+        row * self.width + ((h ^ (h >> 32)) as usize & (self.width - 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_min_sketch_counts_and_ages() {
+        let mut sketch = CountMinSketch::with_capacity(16);
+        for _ in 0..6 {
+            sketch.increment(&1);
+        }
+        sketch.increment(&2);
+        assert!(sketch.frequency(&1) >= 6);
+        assert!(sketch.frequency(&2) >= 1);
+
+        let before = sketch.frequency(&1);
+        sketch.age();
+        assert_eq!(sketch.frequency(&1), before / 2);
+
+        sketch.clear();
+        assert_eq!(sketch.frequency(&1), 0);
+    }
+
+    #[test]
+    fn count_min_sketch_grows_up_to_capacity() {
+        let mut sketch = CountMinSketch::with_capacity(usize::MAX);
+        assert_eq!(sketch.width, 1024);
+
+        sketch.increment(&1);
+        sketch.ensure_capacity(1024);
+        assert_eq!(sketch.frequency(&1), 1);
+
+        sketch.ensure_capacity(3000);
+        assert_eq!(sketch.width, 4096);
+        assert_eq!(sketch.counters.len(), DEPTH * 4096);
+        assert_eq!(sketch.frequency(&1), 0);
+
+        sketch.ensure_capacity(usize::MAX);
+        assert_eq!(sketch.width, MAX_WIDTH);
+
+        let mut bounded = CountMinSketch::with_capacity(100);
+        bounded.ensure_capacity(1000);
+        assert_eq!(bounded.width, 128);
+    }
+}
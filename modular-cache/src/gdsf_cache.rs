@@ -0,0 +1,286 @@
+use crate::{
+    cache::{Cache, GetKeyMut, KeyRegistry},
+    concurrent_cache::ConcurrentCache,
+    key::{KeyExtension, Weighted},
+};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+};
+
+/// Determines the size and cost of a key's value for size-aware registries.
+pub trait Weigher<KeyExt> {
+    fn size(&self, key: &KeyExt) -> usize;
+
+    /// The cost of recomputing the value.
+    fn cost(&self, _key: &KeyExt) -> f64 {
+        1.0
+    }
+}
+
+/// Takes size and cost from the [`Weighted`] key extension.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct KeyWeigher;
+
+impl<KeyExt> Weigher<KeyExt> for KeyWeigher
+where
+    KeyExt: Weighted,
+{
+    fn size(&self, key: &KeyExt) -> usize {
+        key.size()
+    }
+
+    fn cost(&self, key: &KeyExt) -> f64 {
+        key.cost()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Priority(f64);
+
+impl Eq for Priority {}
+
+impl PartialOrd for Priority {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Priority {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+#[derive(Debug)]
+struct GdsfEntry<KeyExt> {
+    key: KeyExt,
+    frequency: usize,
+    size: usize,
+    cost: f64,
+    /// position in the priority queue
+    rank: (Priority, u64),
+}
+
+/// [`GDSF`](https://www.hpl.hp.com/techreports/98/HPL-98-173.pdf) (Greedy-Dual-Size-Frequency)
+/// registry. Here, `max_capacity` is the total size of all keys rather than their number.
+/// Keys are evicted by the lowest priority `clock + frequency * cost / size`, where `clock` is
+/// raised to the priority of each evicted key, such that keys which are not accessed age.
+/// Ties are broken by age. Takes O(log n) for adding, promoting and removing keys.
+#[derive(Debug)]
+pub struct GdsfRegistry<KeyExt, K, W = KeyWeigher>
+where
+    KeyExt: KeyExtension<K>,
+{
+    entries: HashMap<K, GdsfEntry<KeyExt>>,
+    /// keys ordered by priority in ASC order, ties by insertion in ASC order
+    priorities: BTreeMap<(Priority, u64), K>,
+    /// keys evicted by the last `add_or_update` in addition to the returned one
+    evicted: Vec<K>,
+    weigher: W,
+    clock: f64,
+    counter: u64,
+    total_size: usize,
+    max_capacity: usize,
+}
+
+impl<KeyExt, K, W> GdsfRegistry<KeyExt, K, W>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + Clone,
+    W: Weigher<KeyExt>,
+{
+    /// Creates a registry for keys of total size `max_capacity`, weighed by the `weigher`.
+    pub fn with_weigher(max_capacity: usize, weigher: W) -> Self {
+        Self {
+            entries: HashMap::new(),
+            priorities: BTreeMap::new(),
+            evicted: Vec::new(),
+            weigher,
+            clock: 0.0,
+            counter: 0,
+            total_size: 0,
+            max_capacity,
+        }
+    }
+
+    /// The total size of all keys.
+    pub fn total_size(&self) -> usize {
+        self.total_size
+    }
+
+    fn rank(&mut self, frequency: usize, size: usize, cost: f64) -> (Priority, u64) {
+        self.counter += 1;
+        let priority = self.clock + frequency as f64 * cost / size as f64;
+        (Priority(priority), self.counter)
+    }
+
+    fn remove_entry(&mut self, key: &K) -> Option<GdsfEntry<KeyExt>> {
+        let entry = self.entries.remove(key)?;
+        self.priorities.remove(&entry.rank);
+        self.total_size -= entry.size;
+        Some(entry)
+    }
+
+    fn evict(&mut self) -> Option<K> {
+        let ((Priority(priority), _), k) = self.priorities.pop_first()?;
+        self.clock = priority;
+        if let Some(entry) = self.entries.remove(&k) {
+            self.total_size -= entry.size;
+        }
+        Some(k)
+    }
+}
+
+impl<KeyExt, K, W> GetKeyMut<K> for GdsfRegistry<KeyExt, K, W>
+where
+    KeyExt: KeyExtension<K>,
+    K: Eq + Hash + Clone,
+    W: Weigher<KeyExt>,
+{
+    /// Takes O(log n) for incrementing the frequency and updating the priority of the key.
+    fn get(&mut self, key: &K) -> Option<&K> {
+        let (frequency, size, cost, rank) = {
+            let entry = self.entries.get(key)?;
+            (entry.frequency + 1, entry.size, entry.cost, entry.rank)
+        };
+        let new_rank = self.rank(frequency, size, cost);
+        if let Some(k) = self.priorities.remove(&rank) {
+            self.priorities.insert(new_rank, k);
+        }
+
+        let entry = self.entries.get_mut(key)?;
+        entry.frequency = frequency;
+        entry.rank = new_rank;
+        Some(entry.key.key())
+    }
+}
+
+impl<KeyExt, K, W> KeyRegistry<K> for GdsfRegistry<KeyExt, K, W>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + PartialEq + Clone,
+    W: Weigher<KeyExt> + Default,
+{
+    type KeyExtension = KeyExt;
+
+    fn with_capacity(max_capacity: usize) -> Self {
+        Self::with_weigher(max_capacity, W::default())
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.priorities.clear();
+        self.evicted.clear();
+        self.clock = 0.0;
+        self.total_size = 0;
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Takes O(log n) per evicted key. Keys larger than `max_capacity` are rejected.
+    /// An already present key keeps its frequency.
+    fn add_or_update(&mut self, key: Self::KeyExtension) -> Option<K> {
+        self.evicted.clear();
+        let size = self.weigher.size(&key).max(1);
+        let cost = self.weigher.cost(&key);
+        let frequency = self
+            .remove_entry(key.key())
+            .map_or(1, |entry| entry.frequency);
+
+        if size > self.max_capacity {
+            return Some(key.key().clone());
+        }
+
+        let mut deleted_key = None;
+        while self.total_size.saturating_add(size) > self.max_capacity {
+            match self.evict() {
+                Some(k) if deleted_key.is_none() => deleted_key = Some(k),
+                Some(k) => self.evicted.push(k),
+                None => break,
+            }
+        }
+
+        let rank = self.rank(frequency, size, cost);
+        let k = key.key().clone();
+        self.priorities.insert(rank, k.clone());
+        self.entries.insert(
+            k,
+            GdsfEntry {
+                key,
+                frequency,
+                size,
+                cost,
+                rank,
+            },
+        );
+        self.total_size += size;
+        deleted_key
+    }
+
+    fn drain_evicted(&mut self) -> Vec<K> {
+        std::mem::take(&mut self.evicted)
+    }
+
+    /// Takes O(log n).
+    fn try_remove(&mut self, key: &K) -> Option<K> {
+        self.remove_entry(key).map(|entry| entry.key.key().clone())
+    }
+}
+
+pub type GdsfCache<K, KeyExt, V, W = KeyWeigher> = Cache<K, GdsfRegistry<KeyExt, K, W>, KeyExt, V>;
+pub type ConcurrentGdsfCache<K, KeyExt, V, W = KeyWeigher> =
+    ConcurrentCache<K, GdsfRegistry<KeyExt, K, W>, KeyExt, V>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::WeightedKey;
+
+    #[test]
+    fn gdsf_cache_evicts_large_cold_keys() {
+        let mut cache = GdsfCache::<&str, WeightedKey<&str>, String>::new(Some(100));
+        cache.insert(WeightedKey::new("large", 60), "How".to_string());
+        cache.insert(WeightedKey::new("small", 10), "Are".to_string());
+        cache.insert(WeightedKey::with_cost("cheap", 10, 0.5), "You".to_string());
+        cache.get_mut(&"small");
+
+        cache.insert(WeightedKey::new("medium", 30), "Doing".to_string());
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache.get_mut(&"large"), None);
+        assert_eq!(cache.get_mut(&"small").cloned(), Some("Are".to_string()));
+
+        // too large to be cached at all
+        cache.insert(WeightedKey::new("huge", 200), "Doing".to_string());
+        assert_eq!(cache.get_mut(&"huge"), None);
+        assert_eq!(cache.len(), 3);
+    }
+
+    #[derive(Default)]
+    struct KeyAsSize;
+
+    impl Weigher<usize> for KeyAsSize {
+        fn size(&self, key: &usize) -> usize {
+            *key
+        }
+    }
+
+    #[test]
+    fn gdsf_registry_evicts_until_key_fits() {
+        let mut registry = GdsfRegistry::<usize, usize, KeyAsSize>::with_capacity(10);
+        registry.add_or_update(5);
+        registry.add_or_update(4);
+        assert_eq!(registry.total_size(), 9);
+
+        assert_eq!(registry.add_or_update(3), Some(5));
+        assert!(registry.drain_evicted().is_empty());
+
+        assert_eq!(registry.add_or_update(9), Some(4));
+        assert_eq!(registry.drain_evicted(), vec![3]);
+        assert_eq!(registry.total_size(), 9);
+        assert_eq!(registry.len(), 1);
+    }
+}
@@ -0,0 +1,211 @@
+use crate::{
+    cache::{Cache, GetKeyMut, KeyRegistry},
+    concurrent_cache::ConcurrentCache,
+    key::{KeyExtension, Timed},
+    random::XorShiftRng,
+    sampled_slab::SampledSlab,
+};
+use std::{
+    hash::Hash,
+    time::{Duration, Instant},
+};
+
+/// Eviction sampling of the hyperbolic registry.
+#[derive(Debug, Clone, Copy)]
+pub struct HyperbolicConfig {
+    /// number of keys sampled per eviction; all keys are compared if there are not more
+    pub samples: usize,
+    /// seed for sampling reproducibly, random if `None`
+    pub seed: Option<u64>,
+}
+
+impl Default for HyperbolicConfig {
+    /// The sample size used by the hyperbolic caching paper.
+    fn default() -> Self {
+        Self {
+            samples: 64,
+            seed: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct HyperbolicEntry<KeyExt> {
+    key: KeyExt,
+    /// number of accesses, including the insertion
+    hits: usize,
+    /// the instant of the key when it was first inserted
+    since: Instant,
+}
+
+impl<KeyExt, K> KeyExtension<K> for HyperbolicEntry<KeyExt>
+where
+    KeyExt: KeyExtension<K>,
+{
+    fn key(&self) -> &K {
+        self.key.key()
+    }
+}
+
+impl<KeyExt> HyperbolicEntry<KeyExt> {
+    fn priority(&self, now: Instant) -> f64 {
+        let time_in_cache = now
+            .saturating_duration_since(self.since)
+            .max(Duration::from_nanos(1));
+        self.hits as f64 / time_in_cache.as_secs_f64()
+    }
+}
+
+/// [`Hyperbolic caching`](https://www.usenix.org/system/files/conference/atc17/atc17-blankstein.pdf)
+/// registry. The priority of a key is its number of hits divided by its time in the cache,
+/// starting from the key's [`Timed`] instant. Priorities are only computed on eviction, which
+/// samples some keys and evicts the one with the lowest priority; hence they change over time
+/// without ever reordering all keys.
+/// Takes O(1) for finding and removing keys, and O(samples) for evicting keys.
+#[derive(Debug)]
+pub struct HyperbolicRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+{
+    entries: SampledSlab<HyperbolicEntry<KeyExt>, K>,
+    rng: XorShiftRng,
+    samples: usize,
+    max_capacity: usize,
+}
+
+impl<KeyExt, K> HyperbolicRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K> + Timed,
+    K: Hash + Eq + Clone,
+{
+    pub fn with_config(max_capacity: usize, config: HyperbolicConfig) -> Self {
+        let rng = match config.seed {
+            Some(seed) => XorShiftRng::with_seed(seed),
+            None => XorShiftRng::from_entropy(),
+        };
+        Self {
+            entries: SampledSlab::with_capacity(max_capacity),
+            rng,
+            samples: config.samples.max(1),
+            max_capacity,
+        }
+    }
+
+    /// Evicts the key with the lowest priority among the sampled ones.
+    fn evict(&mut self) -> Option<K> {
+        let now = Instant::now();
+        let candidates = self.entries.sample(&mut self.rng, self.samples);
+        let idx = candidates.into_iter().min_by(|&a, &b| {
+            let (a, b) = (self.entries.at(a), self.entries.at(b));
+            a.priority(now).total_cmp(&b.priority(now))
+        })?;
+        Some(self.entries.swap_remove(idx).key.key().clone())
+    }
+}
+
+impl<KeyExt, K> GetKeyMut<K> for HyperbolicRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + Clone,
+{
+    /// Takes O(1) for finding the key and counting the hit.
+    fn get(&mut self, key: &K) -> Option<&K> {
+        let entry = self.entries.get_mut(key)?;
+        entry.hits += 1;
+        Some(entry.key.key())
+    }
+}
+
+impl<KeyExt, K> KeyRegistry<K> for HyperbolicRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K> + Timed,
+    K: Hash + Eq + PartialEq + Clone,
+{
+    type KeyExtension = KeyExt;
+
+    fn with_capacity(max_capacity: usize) -> Self {
+        Self::with_config(max_capacity, HyperbolicConfig::default())
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Takes O(samples) if the registry is full; an already present key is updated in place,
+    /// keeping its hits and time in the cache.
+    fn add_or_update(&mut self, key: Self::KeyExtension) -> Option<K> {
+        if let Some(entry) = self.entries.get_mut(key.key()) {
+            entry.key = key;
+            return None;
+        }
+
+        let deleted_key = if self.len() >= self.max_capacity {
+            self.evict()
+        } else {
+            None
+        };
+
+        self.entries.push(HyperbolicEntry {
+            since: key.instant(),
+            key,
+            hits: 1,
+        });
+        deleted_key
+    }
+
+    /// Takes O(1).
+    fn try_remove(&mut self, key: &K) -> Option<K> {
+        self.entries
+            .remove(key)
+            .map(|entry| entry.key.key().clone())
+    }
+}
+
+pub type HyperbolicCache<K, KeyExt, V> = Cache<K, HyperbolicRegistry<KeyExt, K>, KeyExt, V>;
+pub type ConcurrentHyperbolicCache<K, KeyExt, V> =
+    ConcurrentCache<K, HyperbolicRegistry<KeyExt, K>, KeyExt, V>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::TimedKey;
+    use crate::test_util::timed_key_ago;
+
+    #[test]
+    fn hyperbolic_cache_evicts_rarely_used_keys() {
+        let mut cache = HyperbolicCache::<i32, TimedKey<i32>, String>::new(Some(2));
+        cache.insert(timed_key_ago(1, Duration::from_secs(10)), "How".to_string());
+        cache.insert(timed_key_ago(2, Duration::from_secs(10)), "Are".to_string());
+        cache.get_mut(&1);
+
+        cache.insert(TimedKey::create_now(3), "You".to_string());
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get_mut(&2), None);
+        assert_eq!(cache.get_mut(&1).cloned(), Some("How".to_string()));
+        assert_eq!(cache.get_mut(&3).cloned(), Some("You".to_string()));
+        assert_eq!(cache.remove(&3), Some("You".to_string()));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn hyperbolic_registry_ages_keys() {
+        let mut registry = HyperbolicRegistry::<TimedKey<i32>, i32>::with_capacity(3);
+        registry.add_or_update(timed_key_ago(1, Duration::from_secs(10)));
+        registry.add_or_update(timed_key_ago(2, Duration::from_secs(10)));
+        registry.add_or_update(timed_key_ago(3, Duration::from_secs(20)));
+        for _ in 0..3 {
+            registry.get(&1);
+            registry.get(&3);
+        }
+
+        // priorities are 4 / 10s, 1 / 10s and 4 / 20s
+        assert_eq!(registry.add_or_update(TimedKey::create_now(4)), Some(2));
+        // 3 has as many hits as 1, but spent more time in the cache
+        assert_eq!(registry.add_or_update(TimedKey::create_now(5)), Some(3));
+        assert_eq!(registry.len(), 3);
+    }
+}
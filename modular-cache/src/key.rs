@@ -1,4 +1,4 @@
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 pub trait KeyExtension<K> {
     // type Key = K;
@@ -14,13 +14,13 @@ pub trait KeyExtension<K> {
 
 impl KeyExtension<i32> for i32 {
     fn key(&self) -> &i32 {
-        &self
+        self
     }
 }
 
 impl KeyExtension<usize> for usize {
     fn key(&self) -> &usize {
-        &self
+        self
     }
 }
 
@@ -37,6 +37,10 @@ impl<K> TimedKey<K> {
             instant: Instant::now(),
         }
     }
+
+    pub fn with_instant(key: K, instant: Instant) -> Self {
+        Self { key, instant }
+    }
 }
 
 impl<K> KeyExtension<K> for TimedKey<K> {
@@ -45,3 +49,135 @@ impl<K> KeyExtension<K> for TimedKey<K> {
         &self.key
     }
 }
+
+/// Key extensions which carry a point in time, e.g. for time-aware registries.
+pub trait Timed {
+    fn instant(&self) -> Instant;
+}
+
+impl<K> Timed for TimedKey<K> {
+    fn instant(&self) -> Instant {
+        self.instant
+    }
+}
+
+/// Key extensions which expire at a deadline, e.g. for expiring registries.
+pub trait Expiring {
+    /// The instant the key expires at, or `None` if it never expires.
+    fn deadline(&self) -> Option<Instant>;
+}
+
+#[derive(Clone, Debug)]
+pub struct ExpiringKey<K> {
+    key: K,
+    deadline: Option<Instant>,
+}
+
+impl<K> ExpiringKey<K> {
+    pub fn new(key: K, deadline: Instant) -> Self {
+        Self {
+            key,
+            deadline: Some(deadline),
+        }
+    }
+
+    /// Expires `ttl` from now; a `ttl` which exceeds the range of `Instant` never expires.
+    pub fn expires_in(key: K, ttl: Duration) -> Self {
+        Self {
+            key,
+            deadline: Instant::now().checked_add(ttl),
+        }
+    }
+
+    pub fn never_expiring(key: K) -> Self {
+        Self {
+            key,
+            deadline: None,
+        }
+    }
+}
+
+impl<K> KeyExtension<K> for ExpiringKey<K> {
+    fn key(&self) -> &K {
+        &self.key
+    }
+}
+
+impl<K> Expiring for ExpiringKey<K> {
+    fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+}
+
+/// Key extensions which weigh the cached value, e.g. for size-aware registries.
+pub trait Weighted {
+    fn size(&self) -> usize;
+
+    /// The cost of recomputing the value.
+    fn cost(&self) -> f64 {
+        1.0
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct WeightedKey<K> {
+    key: K,
+    size: usize,
+    cost: f64,
+}
+
+impl<K> WeightedKey<K> {
+    pub fn new(key: K, size: usize) -> Self {
+        Self::with_cost(key, size, 1.0)
+    }
+
+    pub fn with_cost(key: K, size: usize, cost: f64) -> Self {
+        Self { key, size, cost }
+    }
+}
+
+impl<K> KeyExtension<K> for WeightedKey<K> {
+    fn key(&self) -> &K {
+        &self.key
+    }
+}
+
+impl<K> Weighted for WeightedKey<K> {
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn cost(&self) -> f64 {
+        self.cost
+    }
+}
+
+/// Key extensions which carry a priority, e.g. for priority-aware registries.
+pub trait Prioritized {
+    /// Keys of lower priority are evicted first.
+    fn priority(&self) -> u64;
+}
+
+#[derive(Clone, Debug)]
+pub struct PrioritizedKey<K> {
+    key: K,
+    priority: u64,
+}
+
+impl<K> PrioritizedKey<K> {
+    pub fn new(key: K, priority: u64) -> Self {
+        Self { key, priority }
+    }
+}
+
+impl<K> KeyExtension<K> for PrioritizedKey<K> {
+    fn key(&self) -> &K {
+        &self.key
+    }
+}
+
+impl<K> Prioritized for PrioritizedKey<K> {
+    fn priority(&self) -> u64 {
+        self.priority
+    }
+}
@@ -0,0 +1,347 @@
+use crate::{
+    cache::{preallocation, Cache, GetKeyMut, KeyRegistry},
+    concurrent_cache::ConcurrentCache,
+    key::KeyExtension,
+    linked_list::LinkedList,
+    random::XorShiftRng,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+};
+
+/// Learning parameters of the LeCaR registry.
+#[derive(Debug, Clone, Copy)]
+pub struct LecarConfig {
+    /// how strongly a ghost hit shifts the weights
+    pub learning_rate: f64,
+    /// seed for choosing the experts reproducibly, random if `None`
+    pub seed: Option<u64>,
+}
+
+impl Default for LecarConfig {
+    /// The learning rate recommended by the LeCaR paper.
+    fn default() -> Self {
+        Self {
+            learning_rate: 0.45,
+            seed: None,
+        }
+    }
+}
+
+/// The policies among which the LeCaR registry chooses the key to evict.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expert {
+    Lru,
+    Lfu,
+}
+
+#[derive(Debug)]
+struct LecarEntry<KeyExt> {
+    key: KeyExt,
+    /// position in the recency list
+    recency_idx: usize,
+    frequency: usize,
+    /// position in the frequency order
+    rank: (usize, u64),
+}
+
+#[derive(Debug)]
+struct Ghost {
+    /// the expert which chose to evict the key
+    expert: Expert,
+    /// position in the expert's history
+    idx: usize,
+    frequency: usize,
+    evicted_at: u64,
+}
+
+/// [`LeCaR`](https://www.usenix.org/system/files/conference/hotstorage18/hotstorage18-paper-vietri.pdf)
+/// registry, learning whether LRU or LFU suits the workload better. Both experts rank the same
+/// keys and each eviction follows one of them, chosen randomly according to their weights.
+/// Evicted keys are remembered in the chosen expert's history; adding such a key again reveals a
+/// wrong decision and shifts the weights towards the other expert, the more the sooner it happens.
+/// Takes O(1) for finding keys and O(log n) for adding, promoting and removing keys.
+#[derive(Debug)]
+pub struct LecarRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+{
+    entries: HashMap<K, LecarEntry<KeyExt>>,
+    /// keys ordered by recency, i.e. most recently used in front, least recently used in back
+    recency: LinkedList<K>,
+    /// keys ordered by frequency in ASC order, ties by recency
+    frequencies: BTreeMap<(usize, u64), K>,
+    ghosts: HashMap<K, Ghost>,
+    /// keys evicted by LRU, latest in front, earliest in back
+    lru_history: LinkedList<K>,
+    /// keys evicted by LFU, latest in front, earliest in back
+    lfu_history: LinkedList<K>,
+    lru_weight: f64,
+    lfu_weight: f64,
+    last_expert: Option<Expert>,
+    rng: XorShiftRng,
+    learning_rate: f64,
+    /// decay of the reward for a ghost hit per access since the eviction
+    discount_rate: f64,
+    /// logical time, advanced by each access
+    time: u64,
+    max_capacity: usize,
+}
+
+impl<KeyExt, K> LecarRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + Clone,
+{
+    pub fn with_config(max_capacity: usize, config: LecarConfig) -> Self {
+        let rng = match config.seed {
+            Some(seed) => XorShiftRng::with_seed(seed),
+            None => XorShiftRng::from_entropy(),
+        };
+        Self {
+            entries: HashMap::with_capacity(preallocation(max_capacity)),
+            recency: LinkedList::with_capacity(preallocation(max_capacity)),
+            frequencies: BTreeMap::new(),
+            ghosts: HashMap::with_capacity(preallocation(max_capacity)),
+            lru_history: LinkedList::with_capacity(preallocation(max_capacity)),
+            lfu_history: LinkedList::with_capacity(preallocation(max_capacity)),
+            lru_weight: 0.5,
+            lfu_weight: 0.5,
+            last_expert: None,
+            rng,
+            learning_rate: config.learning_rate,
+            discount_rate: 0.005_f64.powf(1.0 / max_capacity.max(1) as f64),
+            time: 0,
+            max_capacity,
+        }
+    }
+
+    /// The current weights of LRU and LFU, summing up to 1.
+    pub fn weights(&self) -> (f64, f64) {
+        (self.lru_weight, self.lfu_weight)
+    }
+
+    /// The expert which chose the last evicted key.
+    pub fn last_expert(&self) -> Option<Expert> {
+        self.last_expert
+    }
+
+    fn history_mut(&mut self, expert: Expert) -> &mut LinkedList<K> {
+        match expert {
+            Expert::Lru => &mut self.lru_history,
+            Expert::Lfu => &mut self.lfu_history,
+        }
+    }
+
+    /// Rewards the expert which did not evict the ghost key.
+    fn learn(&mut self, ghost: &Ghost) {
+        let reward = self
+            .discount_rate
+            .powf(self.time.saturating_sub(ghost.evicted_at) as f64);
+        let factor = (self.learning_rate * reward).exp();
+        match ghost.expert {
+            Expert::Lru => self.lfu_weight *= factor,
+            Expert::Lfu => self.lru_weight *= factor,
+        }
+        let total = self.lru_weight + self.lfu_weight;
+        self.lru_weight /= total;
+        self.lfu_weight /= total;
+    }
+
+    fn evict(&mut self) -> Option<K> {
+        let expert = if self.rng.gen_f64() < self.lru_weight {
+            Expert::Lru
+        } else {
+            Expert::Lfu
+        };
+        let k = match expert {
+            Expert::Lru => self.recency.get(self.recency.back()?).clone(),
+            Expert::Lfu => self.frequencies.first_key_value()?.1.clone(),
+        };
+        let entry = self.entries.remove(&k)?;
+        self.recency.remove(entry.recency_idx);
+        self.frequencies.remove(&entry.rank);
+        self.last_expert = Some(expert);
+
+        let max_capacity = self.max_capacity;
+        let history = self.history_mut(expert);
+        let forgotten = if history.len() >= max_capacity {
+            history.pop_back()
+        } else {
+            None
+        };
+        let idx = history.push_front(k.clone());
+        if let Some(forgotten) = forgotten {
+            self.ghosts.remove(&forgotten);
+        }
+        self.ghosts.insert(
+            k.clone(),
+            Ghost {
+                expert,
+                idx,
+                frequency: entry.frequency,
+                evicted_at: self.time,
+            },
+        );
+        Some(k)
+    }
+}
+
+impl<KeyExt, K> GetKeyMut<K> for LecarRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Eq + Hash + Clone,
+{
+    /// Takes O(log n) for promoting the key and incrementing its frequency.
+    fn get(&mut self, key: &K) -> Option<&K> {
+        let entry = self.entries.get_mut(key)?;
+        self.time += 1;
+        entry.frequency += 1;
+        let rank = (entry.frequency, self.time);
+        if let Some(k) = self.frequencies.remove(&entry.rank) {
+            self.frequencies.insert(rank, k);
+        }
+        entry.rank = rank;
+        self.recency.move_to_front(entry.recency_idx);
+        Some(entry.key.key())
+    }
+}
+
+impl<KeyExt, K> KeyRegistry<K> for LecarRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + PartialEq + Clone,
+{
+    type KeyExtension = KeyExt;
+
+    fn with_capacity(max_capacity: usize) -> Self {
+        Self::with_config(max_capacity, LecarConfig::default())
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+        self.frequencies.clear();
+        self.ghosts.clear();
+        self.lru_history.clear();
+        self.lfu_history.clear();
+        self.lru_weight = 0.5;
+        self.lfu_weight = 0.5;
+        self.last_expert = None;
+        self.time = 0;
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Takes O(log n); an already present key is updated and promoted as on a hit.
+    fn add_or_update(&mut self, key: Self::KeyExtension) -> Option<K> {
+        if let Some(entry) = self.entries.get_mut(key.key()) {
+            let k = key.key().clone();
+            entry.key = key;
+            self.get(&k);
+            return None;
+        }
+
+        self.time += 1;
+        let k = key.key().clone();
+        let frequency = match self.ghosts.remove(&k) {
+            Some(ghost) => {
+                self.history_mut(ghost.expert).remove(ghost.idx);
+                self.learn(&ghost);
+                ghost.frequency + 1
+            }
+            None => 1,
+        };
+
+        let deleted_key = if self.len() >= self.max_capacity {
+            self.evict()
+        } else {
+            None
+        };
+
+        let rank = (frequency, self.time);
+        self.frequencies.insert(rank, k.clone());
+        let recency_idx = self.recency.push_front(k.clone());
+        self.entries.insert(
+            k,
+            LecarEntry {
+                key,
+                recency_idx,
+                frequency,
+                rank,
+            },
+        );
+        deleted_key
+    }
+
+    /// Takes O(log n); ghost keys are not affected.
+    fn try_remove(&mut self, key: &K) -> Option<K> {
+        let entry = self.entries.remove(key)?;
+        self.recency.remove(entry.recency_idx);
+        self.frequencies.remove(&entry.rank);
+        Some(entry.key.key().clone())
+    }
+}
+
+pub type LecarCache<K, KeyExt, V> = Cache<K, LecarRegistry<KeyExt, K>, KeyExt, V>;
+pub type ConcurrentLecarCache<K, KeyExt, V> =
+    ConcurrentCache<K, LecarRegistry<KeyExt, K>, KeyExt, V>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lecar_cache_init() {
+        let mut cache = LecarCache::<i32, i32, String>::new(Some(4));
+        cache.insert(1, "How".to_string());
+        cache.insert(2, "Hi".to_string());
+        cache.insert(3, "Are".to_string());
+        cache.insert(4, "You".to_string());
+        cache.insert(5, "Doing".to_string());
+        cache.insert(2, "How".to_string());
+
+        assert_eq!(cache.len(), 4);
+
+        assert_eq!(cache.get_mut(&1), None);
+        assert_eq!(cache.get_mut(&2).cloned(), Some("How".to_string()));
+        assert_eq!(cache.get_mut(&3).cloned(), Some("Are".to_string()));
+        assert_eq!(cache.get_mut(&4).cloned(), Some("You".to_string()));
+        assert_eq!(cache.get_mut(&5).cloned(), Some("Doing".to_string()));
+
+        // the diagnostics are readable through the cache
+        assert!(cache.key_registry().last_expert().is_some());
+        assert_eq!(cache.key_registry().weights(), (0.5, 0.5));
+    }
+
+    #[test]
+    fn lecar_registry_learns_from_ghost_hits() {
+        let config = LecarConfig {
+            seed: Some(42),
+            ..LecarConfig::default()
+        };
+        let mut registry = LecarRegistry::<i32, i32>::with_config(2, config);
+        registry.add_or_update(1);
+        registry.get(&1);
+        registry.add_or_update(2);
+        assert_eq!(registry.weights(), (0.5, 0.5));
+
+        // LRU would evict 1, the least recently used key, while LFU would evict 2, the least
+        // frequent one; the seed lets LRU choose
+        assert_eq!(registry.add_or_update(3), Some(1));
+        assert_eq!(registry.last_expert(), Some(Expert::Lru));
+
+        // adding the evicted key again one step later shifts the weights towards LFU
+        // by the learning rate discounted once; then LFU chooses 2 over the more recent 3
+        let factor = (0.45 * 0.005_f64.sqrt()).exp();
+        assert_eq!(registry.add_or_update(1), Some(2));
+        assert_eq!(registry.last_expert(), Some(Expert::Lfu));
+        let (lru_weight, lfu_weight) = registry.weights();
+        assert!((lfu_weight - factor / (factor + 1.0)).abs() < 1e-9);
+        assert!((lru_weight - 1.0 / (factor + 1.0)).abs() < 1e-9);
+        assert_eq!(registry.len(), 2);
+    }
+}
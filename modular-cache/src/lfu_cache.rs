@@ -0,0 +1,208 @@
+use crate::{
+    cache::{preallocation, Cache, GetKeyMut, KeyRegistry},
+    concurrent_cache::ConcurrentCache,
+    key::KeyExtension,
+    linked_list::LinkedList,
+};
+use std::{collections::HashMap, hash::Hash};
+
+#[derive(Debug)]
+struct FrequencyBucket<KeyExt> {
+    frequency: usize,
+    /// keys ordered by the time they entered the bucket, i.e. latest in front, earliest in back
+    keys: LinkedList<KeyExt>,
+}
+
+impl<KeyExt> FrequencyBucket<KeyExt> {
+    fn new(frequency: usize) -> Self {
+        Self {
+            frequency,
+            keys: LinkedList::with_capacity(1),
+        }
+    }
+}
+
+/// [`LFU`](https://en.wikipedia.org/wiki/Least_frequently_used) registry,
+/// evicting the least frequently used key. Ties are broken by age, i.e. among the least
+/// frequently used keys the one which reached its frequency first is evicted.
+/// Takes O(1) for finding, counting and removing keys by means of frequency buckets.
+#[derive(Debug)]
+pub struct LfuRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+{
+    /// the bucket of a key and its index within that bucket
+    idx_lookup: HashMap<K, (usize, usize)>,
+    /// buckets ordered by frequency in ASC order, i.e. least frequent in front
+    buckets: LinkedList<FrequencyBucket<KeyExt>>,
+    max_capacity: usize,
+}
+
+impl<KeyExt, K> LfuRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Eq + Hash,
+{
+    /// The number of accesses of the key, starting at 1 when it was added.
+    pub fn frequency(&self, key: &K) -> Option<usize> {
+        self.idx_lookup
+            .get(key)
+            .map(|&(bucket_idx, _)| self.buckets.get(bucket_idx).frequency)
+    }
+
+    /// Moves the key into the bucket of the next higher frequency.
+    fn increment(&mut self, (bucket_idx, key_idx): (usize, usize)) -> (usize, usize) {
+        let frequency = self.buckets.get(bucket_idx).frequency + 1;
+        let next_bucket_idx = match self.buckets.next(bucket_idx) {
+            Some(idx) if self.buckets.get(idx).frequency == frequency => idx,
+            _ => self
+                .buckets
+                .insert_after(bucket_idx, FrequencyBucket::new(frequency)),
+        };
+
+        let key = self.buckets.get_mut(bucket_idx).keys.remove(key_idx);
+        self.remove_if_empty(bucket_idx);
+        let key_idx = self.buckets.get_mut(next_bucket_idx).keys.push_front(key);
+        (next_bucket_idx, key_idx)
+    }
+
+    fn remove_if_empty(&mut self, bucket_idx: usize) {
+        if self.buckets.get(bucket_idx).keys.is_empty() {
+            self.buckets.remove(bucket_idx);
+        }
+    }
+
+    fn delete_least_frequent(&mut self) -> Option<KeyExt> {
+        let bucket_idx = self.buckets.front()?;
+        let key = self.buckets.get_mut(bucket_idx).keys.pop_back();
+        self.remove_if_empty(bucket_idx);
+        if let Some(k) = &key {
+            self.idx_lookup.remove(k.key());
+        }
+        key
+    }
+}
+
+impl<KeyExt, K> GetKeyMut<K> for LfuRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Eq + Hash,
+{
+    /// Takes O(1) for finding the key and incrementing its frequency.
+    fn get(&mut self, key: &K) -> Option<&K> {
+        let position = *self.idx_lookup.get(key)?;
+        let (bucket_idx, key_idx) = self.increment(position);
+        *self.idx_lookup.get_mut(key)? = (bucket_idx, key_idx);
+        Some(self.buckets.get(bucket_idx).keys.get(key_idx).key())
+    }
+}
+
+impl<KeyExt, K> KeyRegistry<K> for LfuRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + PartialEq + Clone,
+{
+    type KeyExtension = KeyExt;
+
+    fn with_capacity(max_capacity: usize) -> Self {
+        Self {
+            idx_lookup: HashMap::with_capacity(preallocation(max_capacity)),
+            buckets: LinkedList::with_capacity(1),
+            max_capacity,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.idx_lookup.clear();
+        self.buckets.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.idx_lookup.len()
+    }
+
+    /// Takes O(1); an already present key is updated and keeps its frequency.
+    fn add_or_update(&mut self, key: Self::KeyExtension) -> Option<K> {
+        if let Some(&(bucket_idx, key_idx)) = self.idx_lookup.get(key.key()) {
+            *self.buckets.get_mut(bucket_idx).keys.get_mut(key_idx) = key;
+            return None;
+        }
+
+        let deleted_key = if self.len() >= self.max_capacity {
+            self.delete_least_frequent().map(|tk| tk.key().clone())
+        } else {
+            None
+        };
+
+        let bucket_idx = match self.buckets.front() {
+            Some(idx) if self.buckets.get(idx).frequency == 1 => idx,
+            _ => self.buckets.push_front(FrequencyBucket::new(1)),
+        };
+        let k = key.key().clone();
+        let key_idx = self.buckets.get_mut(bucket_idx).keys.push_front(key);
+        self.idx_lookup.insert(k, (bucket_idx, key_idx));
+        deleted_key
+    }
+
+    /// Takes O(1).
+    fn try_remove(&mut self, key: &K) -> Option<K> {
+        let (bucket_idx, key_idx) = self.idx_lookup.remove(key)?;
+        let tk = self.buckets.get_mut(bucket_idx).keys.remove(key_idx);
+        self.remove_if_empty(bucket_idx);
+        Some(tk.key().clone())
+    }
+}
+
+pub type LfuCache<K, KeyExt, V> = Cache<K, LfuRegistry<KeyExt, K>, KeyExt, V>;
+pub type ConcurrentLfuCache<K, KeyExt, V> = ConcurrentCache<K, LfuRegistry<KeyExt, K>, KeyExt, V>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lfu_cache_init() {
+        let mut cache = LfuCache::<i32, i32, String>::new(Some(4));
+        cache.insert(1, "How".to_string());
+        cache.insert(2, "Hi".to_string());
+        cache.insert(3, "Are".to_string());
+        cache.insert(4, "You".to_string());
+        cache.insert(5, "Doing".to_string());
+        cache.insert(2, "How".to_string());
+
+        assert_eq!(cache.len(), 4);
+
+        assert_eq!(cache.get_mut(&1), None);
+        assert_eq!(cache.get_mut(&2).cloned(), Some("How".to_string()));
+        assert_eq!(cache.get_mut(&3).cloned(), Some("Are".to_string()));
+        assert_eq!(cache.get_mut(&4).cloned(), Some("You".to_string()));
+        assert_eq!(cache.get_mut(&5).cloned(), Some("Doing".to_string()));
+    }
+
+    #[test]
+    fn lfu_registry_evicts_least_frequent_then_oldest() {
+        let mut registry = LfuRegistry::<i32, i32>::with_capacity(3);
+        registry.add_or_update(1);
+        registry.add_or_update(2);
+        registry.add_or_update(3);
+
+        registry.get(&1);
+        registry.get(&1);
+        registry.get(&3);
+        assert_eq!(registry.frequency(&1), Some(3));
+        assert_eq!(registry.frequency(&2), Some(1));
+        assert_eq!(registry.frequency(&3), Some(2));
+
+        assert_eq!(registry.add_or_update(4), Some(2));
+        // 4 is the only key with frequency 1
+        assert_eq!(registry.add_or_update(5), Some(4));
+
+        registry.get(&5);
+        // 3 and 5 both have frequency 2, but 3 got there first
+        assert_eq!(registry.add_or_update(6), Some(3));
+
+        assert_eq!(registry.try_remove(&1), Some(1));
+        assert_eq!(registry.try_remove(&1), None);
+        assert_eq!(registry.len(), 2);
+    }
+}
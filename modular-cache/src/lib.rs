@@ -1,13 +1,43 @@
+pub mod arc_cache;
+pub mod belady_cache;
 pub mod cache;
+pub mod car_cache;
+pub mod clock_cache;
 pub mod concurrent_cache;
+mod deadline_queue;
+pub mod expiring_cache;
+mod frequency_sketch;
+pub mod gdsf_cache;
+pub mod hyperbolic_cache;
 pub mod key;
+pub mod lecar_cache;
+pub mod lfu_cache;
+mod linked_list;
+pub mod lirs_cache;
+pub mod lru_cache;
+pub mod lru_k_cache;
+pub mod mru_cache;
+pub mod priority_cache;
 pub mod queued_cache;
+mod random;
+pub mod random_cache;
+pub mod s3_fifo_cache;
+pub mod sampled_cache;
+mod sampled_slab;
+pub mod sieve_cache;
+pub mod slru_cache;
+#[cfg(test)]
+mod test_util;
+pub mod timing_wheel;
+pub mod tiny_lfu_cache;
+pub mod tti_cache;
+pub mod ttl_cache;
+pub mod two_queue_cache;
 
 /*
 TODO:
     - split key registry in smaller pieces and try to simplify signature of cache wrt number of generics -> avoid key registry at all!
     - expiration policies
-    - lru impplementation
     - simplest keyregistry with hashmap! -> doesnt make sense
     - concurrent (with std/parkinglot/tokio) via feature
     - rescale capacity
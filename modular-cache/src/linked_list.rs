@@ -0,0 +1,285 @@
+/// Index based doubly linked list, the nodes are kept in a slab.
+/// Handles returned by the `push_*` methods stay valid until the node is removed,
+/// which allows registries to keep them in a lookup and relink nodes in O(1).
+/// Registries which cannot guarantee this use a [`GenerationalList`] instead.
+#[derive(Debug)]
+pub(crate) struct LinkedList<T> {
+    nodes: Vec<Option<Node<T>>>,
+    free: Vec<usize>,
+    /// most recent node
+    head: Option<usize>,
+    /// oldest node
+    tail: Option<usize>,
+    len: usize,
+}
+
+#[derive(Debug)]
+struct Node<T> {
+    value: T,
+    prev: Option<usize>,
+    next: Option<usize>,
+}
+
+impl<T> LinkedList<T> {
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Self {
+            nodes: Vec::with_capacity(capacity),
+            free: Vec::new(),
+            head: None,
+            tail: None,
+            len: 0,
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.nodes.clear();
+        self.free.clear();
+        self.head = None;
+        self.tail = None;
+        self.len = 0;
+    }
+
+    pub(crate) fn front(&self) -> Option<usize> {
+        self.head
+    }
+
+    pub(crate) fn back(&self) -> Option<usize> {
+        self.tail
+    }
+
+    /// The neighbour of `idx` towards the front.
+    pub(crate) fn prev(&self, idx: usize) -> Option<usize> {
+        self.node(idx).prev
+    }
+
+    /// The neighbour of `idx` towards the back.
+    pub(crate) fn next(&self, idx: usize) -> Option<usize> {
+        self.node(idx).next
+    }
+
+    pub(crate) fn get(&self, idx: usize) -> &T {
+        &self.node(idx).value
+    }
+
+    pub(crate) fn get_mut(&mut self, idx: usize) -> &mut T {
+        &mut self.node_mut(idx).value
+    }
+
+    pub(crate) fn push_front(&mut self, value: T) -> usize {
+        let idx = self.allocate(value);
+        self.link_front(idx);
+        idx
+    }
+
+    /// Inserts `value` right behind the node `idx`.
+    pub(crate) fn insert_after(&mut self, idx: usize, value: T) -> usize {
+        let new_idx = self.allocate(value);
+        let next = self.node(idx).next;
+        {
+            let node = self.node_mut(new_idx);
+            node.prev = Some(idx);
+            node.next = next;
+        }
+        self.node_mut(idx).next = Some(new_idx);
+        match next {
+            Some(n) => self.node_mut(n).prev = Some(new_idx),
+            None => self.tail = Some(new_idx),
+        }
+        new_idx
+    }
+
+    pub(crate) fn pop_front(&mut self) -> Option<T> {
+        self.head.map(|idx| self.remove(idx))
+    }
+
+    pub(crate) fn pop_back(&mut self) -> Option<T> {
+        self.tail.map(|idx| self.remove(idx))
+    }
+
+    pub(crate) fn remove(&mut self, idx: usize) -> T {
+        self.unlink(idx);
+        self.len -= 1;
+        self.free.push(idx);
+        self.nodes[idx]
+            .take()
+            .expect("invalid state of linked list")
+            .value
+    }
+
+    pub(crate) fn move_to_front(&mut self, idx: usize) {
+        if self.head != Some(idx) {
+            self.unlink(idx);
+            self.link_front(idx);
+        }
+    }
+
+    fn node(&self, idx: usize) -> &Node<T> {
+        self.nodes[idx]
+            .as_ref()
+            .expect("invalid state of linked list")
+    }
+
+    fn node_mut(&mut self, idx: usize) -> &mut Node<T> {
+        self.nodes[idx]
+            .as_mut()
+            .expect("invalid state of linked list")
+    }
+
+    fn allocate(&mut self, value: T) -> usize {
+        self.len += 1;
+        let node = Node {
+            value,
+            prev: None,
+            next: None,
+        };
+        match self.free.pop() {
+            Some(idx) => {
+                self.nodes[idx] = Some(node);
+                idx
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    fn link_front(&mut self, idx: usize) {
+        let old_head = self.head;
+        {
+            let node = self.node_mut(idx);
+            node.prev = None;
+            node.next = old_head;
+        }
+        match old_head {
+            Some(h) => self.node_mut(h).prev = Some(idx),
+            None => self.tail = Some(idx),
+        }
+        self.head = Some(idx);
+    }
+
+    fn unlink(&mut self, idx: usize) {
+        let (prev, next) = {
+            let node = self.node(idx);
+            (node.prev, node.next)
+        };
+        match prev {
+            Some(p) => self.node_mut(p).next = next,
+            None => self.head = next,
+        }
+        match next {
+            Some(n) => self.node_mut(n).prev = prev,
+            None => self.tail = prev,
+        }
+    }
+}
+
+/// Handle to a node of a [`GenerationalList`]. It is tagged with the generation of the node's
+/// slot, such that it turns stale once the node is removed, even if the slot is reused for another
+/// node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Handle {
+    idx: usize,
+    generation: u64,
+}
+
+/// [`LinkedList`] handing out generation-tagged [`Handle`]s, for registries which may hold on to
+/// handles of removed nodes.
+#[derive(Debug)]
+pub(crate) struct GenerationalList<T> {
+    list: LinkedList<T>,
+    /// generation of each slot, incremented whenever its node is removed
+    generations: Vec<u64>,
+}
+
+impl<T> GenerationalList<T> {
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Self {
+            list: LinkedList::with_capacity(capacity),
+            generations: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Removes all nodes, such that all handles turn stale. Takes O(slots).
+    pub(crate) fn clear(&mut self) {
+        self.list.clear();
+        self.generations.iter_mut().for_each(|g| *g += 1);
+    }
+
+    /// The value of the handle's node, or `None` if the handle is stale.
+    pub(crate) fn get(&self, handle: Handle) -> Option<&T> {
+        self.resolve(handle).map(|idx| self.list.get(idx))
+    }
+
+    pub(crate) fn push_front(&mut self, value: T) -> Handle {
+        let idx = self.list.push_front(value);
+        if idx >= self.generations.len() {
+            self.generations.resize(idx + 1, 0);
+        }
+        Handle {
+            idx,
+            generation: self.generations[idx],
+        }
+    }
+
+    pub(crate) fn pop_back(&mut self) -> Option<T> {
+        let idx = self.list.back()?;
+        Some(self.remove_idx(idx))
+    }
+
+    /// Removes the handle's node, unless the handle is stale.
+    pub(crate) fn remove(&mut self, handle: Handle) -> Option<T> {
+        let idx = self.resolve(handle)?;
+        Some(self.remove_idx(idx))
+    }
+
+    /// The index of the handle's node, or `None` if the handle is stale.
+    fn resolve(&self, handle: Handle) -> Option<usize> {
+        let live = self.generations.get(handle.idx) == Some(&handle.generation);
+        live.then_some(handle.idx)
+    }
+
+    fn remove_idx(&mut self, idx: usize) -> T {
+        self.generations[idx] += 1;
+        self.list.remove(idx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generational_list_invalidates_handles_of_reused_nodes() {
+        let mut list = GenerationalList::with_capacity(2);
+        let first = list.push_front(1);
+        let second = list.push_front(2);
+        assert_eq!(list.remove(first), Some(1));
+        assert_eq!(list.get(first), None);
+
+        // the slot of 1 is reused, but its handle stays stale
+        let third = list.push_front(3);
+        assert_eq!(list.get(first), None);
+        assert_eq!(list.remove(first), None);
+        assert_eq!(list.get(third), Some(&3));
+        assert_eq!(list.len(), 2);
+
+        assert_eq!(list.pop_back(), Some(2));
+        assert_eq!(list.get(second), None);
+        list.clear();
+        assert_eq!(list.get(third), None);
+        assert_eq!(list.len(), 0);
+    }
+}
@@ -0,0 +1,328 @@
+use crate::{
+    cache::{preallocation, Cache, GetKeyMut, KeyRegistry},
+    concurrent_cache::ConcurrentCache,
+    key::KeyExtension,
+    linked_list::LinkedList,
+};
+use std::{collections::HashMap, hash::Hash};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    /// low inter-reference recency, always resident
+    Lir,
+    /// high inter-reference recency and resident
+    ResidentHir,
+    /// high inter-reference recency, only kept as metadata in the stack
+    NonResidentHir,
+}
+
+#[derive(Debug)]
+struct LirsEntry<KeyExt> {
+    /// `None` for non-resident keys
+    key: Option<KeyExt>,
+    status: Status,
+    /// position in the stack S
+    stack_idx: Option<usize>,
+    /// position in the queue Q for resident keys, or among the non-resident keys otherwise
+    queue_idx: Option<usize>,
+}
+
+/// [`LIRS`](https://en.wikipedia.org/wiki/LIRS_caching_algorithm) registry.
+/// Keys are separated by their inter-reference recency into LIR keys, which take about 99% of
+/// `max_capacity`, and HIR keys. The stack S tracks the recency of LIR keys as well as resident
+/// and non-resident HIR keys; the queue Q holds the resident HIR keys, which are evicted first.
+/// A HIR key which is accessed again while still in S turns into a LIR key.
+/// Takes O(1) for finding and removing keys, and amortized O(1) for promoting keys.
+#[derive(Debug)]
+pub struct LirsRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+{
+    entries: HashMap<K, LirsEntry<KeyExt>>,
+    /// the stack S ordered by recency, i.e. top in front, bottom in back
+    stack: LinkedList<K>,
+    /// the queue Q of resident HIR keys, i.e. latest in front, earliest in back
+    queue: LinkedList<K>,
+    /// non-resident HIR keys ordered by eviction, i.e. latest in front, earliest in back
+    non_resident: LinkedList<K>,
+    lir_len: usize,
+    lir_capacity: usize,
+    max_capacity: usize,
+}
+
+impl<KeyExt, K> LirsRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + Clone,
+{
+    fn push_stack(&mut self, k: &K) {
+        let stack_idx = self.entries.get(k).and_then(|entry| entry.stack_idx);
+        match stack_idx {
+            Some(idx) => self.stack.move_to_front(idx),
+            None => {
+                let idx = self.stack.push_front(k.clone());
+                if let Some(entry) = self.entries.get_mut(k) {
+                    entry.stack_idx = Some(idx);
+                }
+            }
+        }
+    }
+
+    /// Removes HIR keys from the bottom of S, such that a LIR key is at the bottom.
+    fn prune(&mut self) {
+        while let Some(idx) = self.stack.back() {
+            let entry = match self.entries.get_mut(self.stack.get(idx)) {
+                Some(entry) if entry.status != Status::Lir => entry,
+                _ => break,
+            };
+            entry.stack_idx = None;
+            let (status, queue_idx) = (entry.status, entry.queue_idx);
+            let k = self.stack.remove(idx);
+            if status == Status::NonResidentHir {
+                if let Some(queue_idx) = queue_idx {
+                    self.non_resident.remove(queue_idx);
+                }
+                self.entries.remove(&k);
+            }
+        }
+    }
+
+    /// Turns the LIR key at the bottom of S into a resident HIR key.
+    fn demote_bottom_lir(&mut self) {
+        self.prune();
+        let Some(k) = self.stack.pop_back() else {
+            return;
+        };
+        let queue_idx = self.queue.push_front(k.clone());
+        if let Some(entry) = self.entries.get_mut(&k) {
+            entry.status = Status::ResidentHir;
+            entry.stack_idx = None;
+            entry.queue_idx = Some(queue_idx);
+        }
+        self.lir_len -= 1;
+        self.prune();
+    }
+
+    /// Evicts the earliest resident HIR key and keeps it as metadata if it is still in S.
+    fn evict(&mut self) -> Option<K> {
+        if self.queue.is_empty() {
+            self.demote_bottom_lir();
+        }
+        let k = self.queue.pop_back()?;
+        let entry = self.entries.get_mut(&k)?;
+        if entry.stack_idx.is_none() {
+            self.entries.remove(&k);
+            return Some(k);
+        }
+
+        entry.key = None;
+        entry.status = Status::NonResidentHir;
+        entry.queue_idx = Some(self.non_resident.push_front(k.clone()));
+
+        if self.non_resident.len() > self.max_capacity {
+            if let Some(forgotten) = self.non_resident.pop_back() {
+                if let Some(stack_idx) = self
+                    .entries
+                    .remove(&forgotten)
+                    .and_then(|entry| entry.stack_idx)
+                {
+                    self.stack.remove(stack_idx);
+                }
+                self.prune();
+            }
+        }
+        Some(k)
+    }
+}
+
+impl<KeyExt, K> GetKeyMut<K> for LirsRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Eq + Hash + Clone,
+{
+    /// Takes O(1) for finding the key and amortized O(1) for updating the stack and queue.
+    fn get(&mut self, key: &K) -> Option<&K> {
+        let entry = self.entries.get(key)?;
+        let (status, stack_idx, queue_idx) = (entry.status, entry.stack_idx, entry.queue_idx);
+        match status {
+            Status::NonResidentHir => return None,
+            Status::Lir => {
+                let was_bottom = stack_idx.is_some() && self.stack.back() == stack_idx;
+                self.push_stack(key);
+                if was_bottom {
+                    self.prune();
+                }
+            }
+            Status::ResidentHir if stack_idx.is_some() => {
+                if let Some(queue_idx) = queue_idx {
+                    self.queue.remove(queue_idx);
+                }
+                if let Some(entry) = self.entries.get_mut(key) {
+                    entry.status = Status::Lir;
+                    entry.queue_idx = None;
+                }
+                self.lir_len += 1;
+                self.push_stack(key);
+                if self.lir_len > self.lir_capacity {
+                    self.demote_bottom_lir();
+                }
+            }
+            Status::ResidentHir => {
+                if let Some(queue_idx) = queue_idx {
+                    self.queue.move_to_front(queue_idx);
+                }
+                self.push_stack(key);
+            }
+        }
+        self.entries
+            .get(key)
+            .and_then(|entry| entry.key.as_ref())
+            .map(|tk| tk.key())
+    }
+}
+
+impl<KeyExt, K> KeyRegistry<K> for LirsRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + PartialEq + Clone,
+{
+    type KeyExtension = KeyExt;
+
+    fn with_capacity(max_capacity: usize) -> Self {
+        let hir_capacity = (max_capacity / 100).max(1);
+        Self {
+            entries: HashMap::with_capacity(preallocation(max_capacity)),
+            stack: LinkedList::with_capacity(preallocation(max_capacity)),
+            queue: LinkedList::with_capacity(preallocation(hir_capacity)),
+            non_resident: LinkedList::with_capacity(preallocation(max_capacity)),
+            lir_len: 0,
+            lir_capacity: max_capacity.saturating_sub(hir_capacity),
+            max_capacity,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.stack.clear();
+        self.queue.clear();
+        self.non_resident.clear();
+        self.lir_len = 0;
+    }
+
+    fn len(&self) -> usize {
+        self.lir_len + self.queue.len()
+    }
+
+    /// Takes amortized O(1); an already present key is updated in place.
+    fn add_or_update(&mut self, key: Self::KeyExtension) -> Option<K> {
+        if let Some(entry) = self.entries.get_mut(key.key()) {
+            if entry.key.is_some() {
+                entry.key = Some(key);
+                return None;
+            }
+        }
+
+        let deleted_key = if self.len() >= self.max_capacity {
+            self.evict()
+        } else {
+            None
+        };
+
+        let k = key.key().clone();
+        let non_resident = self.entries.get(&k).and_then(|entry| entry.queue_idx);
+        if let Some(queue_idx) = non_resident {
+            self.non_resident.remove(queue_idx);
+        }
+        let entry = self.entries.entry(k.clone()).or_insert(LirsEntry {
+            key: None,
+            status: Status::NonResidentHir,
+            stack_idx: None,
+            queue_idx: None,
+        });
+        entry.key = Some(key);
+        entry.queue_idx = None;
+
+        if self.lir_len < self.lir_capacity || non_resident.is_some() {
+            entry.status = Status::Lir;
+            self.lir_len += 1;
+            self.push_stack(&k);
+            if self.lir_len > self.lir_capacity {
+                self.demote_bottom_lir();
+            }
+        } else {
+            entry.status = Status::ResidentHir;
+            entry.queue_idx = Some(self.queue.push_front(k.clone()));
+            self.push_stack(&k);
+        }
+        deleted_key
+    }
+
+    /// Takes amortized O(1); non-resident keys are not affected.
+    fn try_remove(&mut self, key: &K) -> Option<K> {
+        if self.entries.get(key)?.status == Status::NonResidentHir {
+            return None;
+        }
+        let entry = self.entries.remove(key)?;
+        if let Some(stack_idx) = entry.stack_idx {
+            self.stack.remove(stack_idx);
+        }
+        if let Some(queue_idx) = entry.queue_idx {
+            self.queue.remove(queue_idx);
+        }
+        if entry.status == Status::Lir {
+            self.lir_len -= 1;
+        }
+        self.prune();
+        entry.key.map(|tk| tk.key().clone())
+    }
+}
+
+pub type LirsCache<K, KeyExt, V> = Cache<K, LirsRegistry<KeyExt, K>, KeyExt, V>;
+pub type ConcurrentLirsCache<K, KeyExt, V> = ConcurrentCache<K, LirsRegistry<KeyExt, K>, KeyExt, V>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lirs_registry_promotes_non_resident_hir_keys() {
+        let mut registry = LirsRegistry::<i32, i32>::with_capacity(4);
+        registry.add_or_update(1);
+        registry.add_or_update(2);
+        registry.add_or_update(3);
+        // 3 LIR keys fill the LIR capacity, 4 becomes a resident HIR key
+        assert_eq!(registry.add_or_update(4), None);
+        // 4 is evicted, but remains as non-resident HIR key in the stack
+        assert_eq!(registry.add_or_update(5), Some(4));
+        assert_eq!(registry.get(&4), None);
+
+        // 4 turns into a LIR key, while the bottom LIR key 1 is demoted to a resident HIR key
+        assert_eq!(registry.add_or_update(4), Some(5));
+        assert_eq!(registry.get(&1), Some(&1));
+        assert_eq!(registry.len(), 4);
+
+        // the resident HIR key 1 is evicted first
+        assert_eq!(registry.add_or_update(6), Some(1));
+        assert_eq!(registry.try_remove(&1), None);
+        assert_eq!(registry.try_remove(&4), Some(4));
+        assert_eq!(registry.len(), 3);
+    }
+
+    #[test]
+    fn lirs_cache_hits_on_cyclic_access() {
+        let mut cache = LirsCache::<usize, usize, usize>::new(Some(4));
+        let mut hits = 0;
+
+        for _ in 0..10 {
+            for k in 0..5 {
+                match cache.get_mut(&k) {
+                    Some(_) => hits += 1,
+                    None => _ = cache.insert(k, k),
+                }
+            }
+        }
+
+        assert_eq!(cache.len(), 4);
+        assert!(hits > 25);
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{
+    cache::{preallocation, Cache, GetKeyMut, KeyRegistry},
+    concurrent_cache::ConcurrentCache,
+    key::KeyExtension,
+    linked_list::LinkedList,
+};
+use std::{collections::HashMap, hash::Hash};
+
+/// [`LRU`](https://en.wikipedia.org/wiki/Cache_replacement_policies#LRU) registry,
+/// evicting the least recently used key.
+/// Takes O(1) for finding, promoting and removing keys.
+#[derive(Debug)]
+pub struct LruRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+{
+    idx_lookup: HashMap<K, usize>,
+    /// keys ordered by recency, i.e. most recently used in front, least recently used in back
+    ordered_keys: LinkedList<KeyExt>,
+    max_capacity: usize,
+}
+
+impl<KeyExt, K> GetKeyMut<K> for LruRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Eq + Hash,
+{
+    /// Takes O(1) for finding the key and promoting it to the most recently used one.
+    fn get(&mut self, key: &K) -> Option<&K> {
+        let idx = *self.idx_lookup.get(key)?;
+        self.ordered_keys.move_to_front(idx);
+        Some(self.ordered_keys.get(idx).key())
+    }
+}
+
+impl<KeyExt, K> KeyRegistry<K> for LruRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + PartialEq + Clone,
+{
+    type KeyExtension = KeyExt;
+
+    fn with_capacity(max_capacity: usize) -> Self {
+        Self {
+            idx_lookup: HashMap::with_capacity(preallocation(max_capacity)),
+            ordered_keys: LinkedList::with_capacity(preallocation(max_capacity)),
+            max_capacity,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.idx_lookup.clear();
+        self.ordered_keys.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.ordered_keys.len()
+    }
+
+    /// Takes O(1); an already present key is updated and promoted.
+    fn add_or_update(&mut self, key: Self::KeyExtension) -> Option<K> {
+        if let Some(&idx) = self.idx_lookup.get(key.key()) {
+            *self.ordered_keys.get_mut(idx) = key;
+            self.ordered_keys.move_to_front(idx);
+            return None;
+        }
+
+        let deleted_key = if self.len() >= self.max_capacity {
+            self.ordered_keys.pop_back().map(|tk| {
+                self.idx_lookup.remove(tk.key());
+                tk.key().clone()
+            })
+        } else {
+            None
+        };
+
+        let k = key.key().clone();
+        let idx = self.ordered_keys.push_front(key);
+        self.idx_lookup.insert(k, idx);
+        deleted_key
+    }
+
+    /// Takes O(1).
+    fn try_remove(&mut self, key: &K) -> Option<K> {
+        self.idx_lookup
+            .remove(key)
+            .map(|idx| self.ordered_keys.remove(idx).key().clone())
+    }
+}
+
+pub type LruCache<K, KeyExt, V> = Cache<K, LruRegistry<KeyExt, K>, KeyExt, V>;
+pub type ConcurrentLruCache<K, KeyExt, V> = ConcurrentCache<K, LruRegistry<KeyExt, K>, KeyExt, V>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lru_cache_init() {
+        let mut cache = LruCache::<i32, i32, String>::new(Some(4));
+        cache.insert(1, "How".to_string());
+        cache.insert(2, "Hi".to_string());
+        cache.insert(3, "Are".to_string());
+        cache.insert(4, "You".to_string());
+        cache.insert(5, "Doing".to_string());
+        cache.insert(2, "How".to_string());
+
+        assert_eq!(cache.len(), 4);
+
+        assert_eq!(cache.get_mut(&1), None);
+        assert_eq!(cache.get_mut(&2).cloned(), Some("How".to_string()));
+        assert_eq!(cache.get_mut(&3).cloned(), Some("Are".to_string()));
+        assert_eq!(cache.get_mut(&4).cloned(), Some("You".to_string()));
+        assert_eq!(cache.get_mut(&5).cloned(), Some("Doing".to_string()));
+    }
+
+    #[test]
+    fn lru_cache_evicts_least_recently_used() {
+        let mut cache = LruCache::<i32, i32, String>::new(Some(3));
+        cache.insert(1, "How".to_string());
+        cache.insert(2, "Are".to_string());
+        cache.insert(3, "You".to_string());
+
+        // promote 1, such that 2 is the least recently used
+        assert_eq!(cache.get_mut(&1).cloned(), Some("How".to_string()));
+        cache.insert(4, "Doing".to_string());
+
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache.get_mut(&2), None);
+        assert_eq!(cache.get_mut(&1).cloned(), Some("How".to_string()));
+
+        assert_eq!(cache.remove(&3), Some("You".to_string()));
+        assert_eq!(cache.remove(&3), None);
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn lru_cache_unbounded() {
+        let mut cache = LruCache::<i32, i32, String>::new(None);
+        for key in 0..2048 {
+            cache.insert(key, key.to_string());
+        }
+        assert_eq!(cache.len(), 2048);
+        assert_eq!(cache.get_mut(&0).cloned(), Some("0".to_string()));
+    }
+
+    #[test]
+    fn concurrent_lru_cache_init() {
+        let mut cache = ConcurrentLruCache::<i32, i32, String>::new(Some(2));
+        cache.insert(1, "How".to_string());
+        cache.insert(2, "Are".to_string());
+        assert_eq!(cache.get_mut(&1).as_deref(), Some(&"How".to_string()));
+        cache.insert(3, "You".to_string());
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get_mut(&2), None);
+        assert_eq!(cache.get_mut(&1).as_deref(), Some(&"How".to_string()));
+        assert_eq!(cache.get_mut(&3).as_deref(), Some(&"You".to_string()));
+    }
+}
@@ -0,0 +1,261 @@
+use crate::{
+    cache::{preallocation, Cache, GetKeyMut, KeyRegistry},
+    concurrent_cache::ConcurrentCache,
+    key::KeyExtension,
+    linked_list::LinkedList,
+};
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    hash::Hash,
+};
+
+/// Parameters of the LRU-K registry.
+#[derive(Debug, Clone, Copy)]
+pub struct LruKConfig {
+    /// number of most recent accesses which are remembered per key
+    pub k: usize,
+    /// number of evicted keys whose accesses are remembered, as fraction of `max_capacity`
+    pub history_fraction: f64,
+}
+
+impl Default for LruKConfig {
+    /// LRU-2, which the LRU-K paper found to discriminate well between frequently and rarely
+    /// accessed keys.
+    fn default() -> Self {
+        Self {
+            k: 2,
+            history_fraction: 1.0,
+        }
+    }
+}
+
+/// Position in the eviction order: keys with fewer than K accesses first, ordered by their
+/// last access, then all others ordered by their K-th most recent access.
+type Rank = (bool, u64);
+
+#[derive(Debug)]
+struct LruKEntry<KeyExt> {
+    key: KeyExt,
+    /// the most recent access times, latest in front
+    accesses: VecDeque<u64>,
+    rank: Rank,
+}
+
+/// [`LRU-K`](https://www.cs.cmu.edu/~christos/courses/721-resources/p297-o_neil.pdf) registry,
+/// evicting the key whose K-th most recent access is the oldest. Keys with fewer than K accesses
+/// are evicted first, such that keys accessed only once do not push out repeatedly accessed ones.
+/// The accesses of recently evicted keys are remembered in a bounded history and restored if the
+/// key is added again.
+/// Takes O(1) for finding keys and O(log n) for adding, promoting and removing keys.
+#[derive(Debug)]
+pub struct LruKRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+{
+    entries: HashMap<K, LruKEntry<KeyExt>>,
+    /// keys in eviction order
+    ranks: BTreeMap<Rank, K>,
+    /// accesses of evicted keys and their position in `history_order`
+    history: HashMap<K, (VecDeque<u64>, usize)>,
+    /// evicted keys ordered by eviction, i.e. latest in front, earliest in back
+    history_order: LinkedList<K>,
+    history_capacity: usize,
+    /// logical time, advanced by each access
+    clock: u64,
+    k: usize,
+    max_capacity: usize,
+}
+
+impl<KeyExt, K> LruKRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + Clone,
+{
+    pub fn with_config(max_capacity: usize, config: LruKConfig) -> Self {
+        let history_capacity = (max_capacity as f64 * config.history_fraction) as usize;
+        Self {
+            entries: HashMap::with_capacity(preallocation(max_capacity)),
+            ranks: BTreeMap::new(),
+            history: HashMap::with_capacity(preallocation(history_capacity)),
+            history_order: LinkedList::with_capacity(preallocation(history_capacity)),
+            history_capacity,
+            clock: 0,
+            k: config.k.max(1),
+            max_capacity,
+        }
+    }
+
+    /// Records an access and returns the key's new rank.
+    fn access(&mut self, accesses: &mut VecDeque<u64>) -> Rank {
+        self.clock += 1;
+        accesses.push_front(self.clock);
+        accesses.truncate(self.k);
+        match accesses.get(self.k - 1) {
+            Some(&kth_access) => (true, kth_access),
+            None => (false, self.clock),
+        }
+    }
+
+    fn remember(&mut self, k: K, accesses: VecDeque<u64>) {
+        if self.history_capacity == 0 {
+            return;
+        }
+        if self.history_order.len() >= self.history_capacity {
+            if let Some(forgotten) = self.history_order.pop_back() {
+                self.history.remove(&forgotten);
+            }
+        }
+        let idx = self.history_order.push_front(k.clone());
+        self.history.insert(k, (accesses, idx));
+    }
+
+    fn evict(&mut self) -> Option<K> {
+        let (_, k) = self.ranks.pop_first()?;
+        if let Some(entry) = self.entries.remove(&k) {
+            self.remember(k.clone(), entry.accesses);
+        }
+        Some(k)
+    }
+}
+
+impl<KeyExt, K> GetKeyMut<K> for LruKRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Eq + Hash + Clone,
+{
+    /// Takes O(log n) for recording the access and updating the key's rank.
+    fn get(&mut self, key: &K) -> Option<&K> {
+        let (mut accesses, rank) = {
+            let entry = self.entries.get_mut(key)?;
+            (std::mem::take(&mut entry.accesses), entry.rank)
+        };
+        let new_rank = self.access(&mut accesses);
+        if let Some(k) = self.ranks.remove(&rank) {
+            self.ranks.insert(new_rank, k);
+        }
+
+        let entry = self.entries.get_mut(key)?;
+        entry.accesses = accesses;
+        entry.rank = new_rank;
+        Some(entry.key.key())
+    }
+}
+
+impl<KeyExt, K> KeyRegistry<K> for LruKRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + PartialEq + Clone,
+{
+    type KeyExtension = KeyExt;
+
+    fn with_capacity(max_capacity: usize) -> Self {
+        Self::with_config(max_capacity, LruKConfig::default())
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.ranks.clear();
+        self.history.clear();
+        self.history_order.clear();
+        self.clock = 0;
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Takes O(log n); an already present key is updated in place and its access is recorded.
+    fn add_or_update(&mut self, key: Self::KeyExtension) -> Option<K> {
+        if self.entries.contains_key(key.key()) {
+            let k = key.key().clone();
+            if let Some(entry) = self.entries.get_mut(&k) {
+                entry.key = key;
+            }
+            self.get(&k);
+            return None;
+        }
+
+        let deleted_key = if self.len() >= self.max_capacity {
+            self.evict()
+        } else {
+            None
+        };
+
+        let k = key.key().clone();
+        let mut accesses = match self.history.remove(&k) {
+            Some((accesses, idx)) => {
+                self.history_order.remove(idx);
+                accesses
+            }
+            None => VecDeque::with_capacity(self.k),
+        };
+        let rank = self.access(&mut accesses);
+        self.ranks.insert(rank, k.clone());
+        self.entries.insert(
+            k,
+            LruKEntry {
+                key,
+                accesses,
+                rank,
+            },
+        );
+        deleted_key
+    }
+
+    /// Takes O(log n); the key's accesses are forgotten.
+    fn try_remove(&mut self, key: &K) -> Option<K> {
+        let entry = self.entries.remove(key)?;
+        self.ranks.remove(&entry.rank);
+        Some(entry.key.key().clone())
+    }
+}
+
+pub type LruKCache<K, KeyExt, V> = Cache<K, LruKRegistry<KeyExt, K>, KeyExt, V>;
+pub type ConcurrentLruKCache<K, KeyExt, V> = ConcurrentCache<K, LruKRegistry<KeyExt, K>, KeyExt, V>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lru_k_cache_init() {
+        let mut cache = LruKCache::<i32, i32, String>::new(Some(4));
+        cache.insert(1, "How".to_string());
+        cache.insert(2, "Hi".to_string());
+        cache.insert(3, "Are".to_string());
+        cache.insert(4, "You".to_string());
+        cache.insert(5, "Doing".to_string());
+        cache.insert(2, "How".to_string());
+
+        assert_eq!(cache.len(), 4);
+
+        assert_eq!(cache.get_mut(&1), None);
+        assert_eq!(cache.get_mut(&2).cloned(), Some("How".to_string()));
+        assert_eq!(cache.get_mut(&3).cloned(), Some("Are".to_string()));
+        assert_eq!(cache.get_mut(&4).cloned(), Some("You".to_string()));
+        assert_eq!(cache.get_mut(&5).cloned(), Some("Doing".to_string()));
+    }
+
+    #[test]
+    fn lru_k_registry_evicts_by_kth_access() {
+        let mut registry = LruKRegistry::<i32, i32>::with_capacity(3);
+        registry.add_or_update(1);
+        registry.add_or_update(2);
+        registry.add_or_update(3);
+        registry.get(&1);
+        registry.get(&2);
+
+        // 3 was accessed fewer than 2 times
+        assert_eq!(registry.add_or_update(4), Some(3));
+        registry.get(&4);
+        // 1 has the oldest second most recent access
+        assert_eq!(registry.add_or_update(5), Some(1));
+
+        // 3 regains its first access from the history, 5 was accessed only once
+        assert_eq!(registry.add_or_update(3), Some(5));
+        assert_eq!(registry.add_or_update(6), Some(2));
+
+        assert_eq!(registry.try_remove(&3), Some(3));
+        assert_eq!(registry.len(), 2);
+    }
+}
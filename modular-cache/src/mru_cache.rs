@@ -0,0 +1,140 @@
+use crate::{
+    cache::{preallocation, Cache, GetKeyMut, KeyRegistry},
+    concurrent_cache::ConcurrentCache,
+    key::KeyExtension,
+    linked_list::LinkedList,
+};
+use std::{collections::HashMap, hash::Hash};
+
+/// [`MRU`](https://en.wikipedia.org/wiki/Cache_replacement_policies#Most-recently-used_(MRU))
+/// registry, evicting the most recently used key.
+/// Unlike LRU, it keeps most of the keys when cyclically accessing slightly more keys than fit.
+/// Takes O(1) for finding, promoting and removing keys.
+#[derive(Debug)]
+pub struct MruRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+{
+    idx_lookup: HashMap<K, usize>,
+    /// keys ordered by recency, i.e. most recently used in front, least recently used in back
+    ordered_keys: LinkedList<KeyExt>,
+    max_capacity: usize,
+}
+
+impl<KeyExt, K> GetKeyMut<K> for MruRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Eq + Hash,
+{
+    /// Takes O(1) for finding the key and promoting it to the most recently used one.
+    fn get(&mut self, key: &K) -> Option<&K> {
+        let idx = *self.idx_lookup.get(key)?;
+        self.ordered_keys.move_to_front(idx);
+        Some(self.ordered_keys.get(idx).key())
+    }
+}
+
+impl<KeyExt, K> KeyRegistry<K> for MruRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + PartialEq + Clone,
+{
+    type KeyExtension = KeyExt;
+
+    fn with_capacity(max_capacity: usize) -> Self {
+        Self {
+            idx_lookup: HashMap::with_capacity(preallocation(max_capacity)),
+            ordered_keys: LinkedList::with_capacity(preallocation(max_capacity)),
+            max_capacity,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.idx_lookup.clear();
+        self.ordered_keys.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.ordered_keys.len()
+    }
+
+    /// Takes O(1); an already present key is updated and promoted.
+    fn add_or_update(&mut self, key: Self::KeyExtension) -> Option<K> {
+        if let Some(&idx) = self.idx_lookup.get(key.key()) {
+            *self.ordered_keys.get_mut(idx) = key;
+            self.ordered_keys.move_to_front(idx);
+            return None;
+        }
+
+        let deleted_key = if self.len() >= self.max_capacity {
+            self.ordered_keys.pop_front().map(|tk| {
+                self.idx_lookup.remove(tk.key());
+                tk.key().clone()
+            })
+        } else {
+            None
+        };
+
+        let k = key.key().clone();
+        let idx = self.ordered_keys.push_front(key);
+        self.idx_lookup.insert(k, idx);
+        deleted_key
+    }
+
+    /// Takes O(1).
+    fn try_remove(&mut self, key: &K) -> Option<K> {
+        self.idx_lookup
+            .remove(key)
+            .map(|idx| self.ordered_keys.remove(idx).key().clone())
+    }
+}
+
+pub type MruCache<K, KeyExt, V> = Cache<K, MruRegistry<KeyExt, K>, KeyExt, V>;
+pub type ConcurrentMruCache<K, KeyExt, V> = ConcurrentCache<K, MruRegistry<KeyExt, K>, KeyExt, V>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lru_cache::LruCache;
+
+    #[test]
+    fn mru_cache_evicts_most_recently_used() {
+        let mut cache = MruCache::<i32, i32, String>::new(Some(3));
+        cache.insert(1, "How".to_string());
+        cache.insert(2, "Are".to_string());
+        cache.insert(3, "You".to_string());
+
+        assert_eq!(cache.get_mut(&2).cloned(), Some("Are".to_string()));
+        cache.insert(4, "Doing".to_string());
+
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache.get_mut(&2), None);
+        assert_eq!(cache.get_mut(&1).cloned(), Some("How".to_string()));
+        assert_eq!(cache.get_mut(&3).cloned(), Some("You".to_string()));
+        assert_eq!(cache.remove(&4), Some("Doing".to_string()));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn mru_cache_hits_on_cyclic_access() {
+        let mut mru_cache = MruCache::<usize, usize, usize>::new(Some(4));
+        let mut lru_cache = LruCache::<usize, usize, usize>::new(Some(4));
+        let (mut mru_hits, mut lru_hits) = (0, 0);
+
+        for _ in 0..10 {
+            for k in 0..5 {
+                match mru_cache.get_mut(&k) {
+                    Some(_) => mru_hits += 1,
+                    None => _ = mru_cache.insert(k, k),
+                }
+                match lru_cache.get_mut(&k) {
+                    Some(_) => lru_hits += 1,
+                    None => _ = lru_cache.insert(k, k),
+                }
+            }
+        }
+
+        assert_eq!(lru_hits, 0);
+        assert!(mru_hits > 25);
+    }
+}
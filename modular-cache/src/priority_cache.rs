@@ -0,0 +1,220 @@
+use crate::{
+    cache::{preallocation, Cache, GetKeyMut, KeyRegistry},
+    concurrent_cache::ConcurrentCache,
+    key::{KeyExtension, Prioritized},
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+};
+
+/// Position in the eviction order: the priority, then the last use.
+type Rank = (u64, u64);
+
+#[derive(Debug)]
+struct PriorityEntry<KeyExt> {
+    key: KeyExt,
+    rank: Rank,
+}
+
+/// Registry evicting the key of lowest priority, as given by its [`Prioritized`] extension or
+/// changed by `set_priority`. Among keys of equal priority, the least recently used one is evicted.
+/// Takes O(log n) for finding, promoting, adding and removing keys.
+#[derive(Debug)]
+pub struct PriorityRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+{
+    entries: HashMap<K, PriorityEntry<KeyExt>>,
+    /// keys ordered by priority in ASC order, ties by recency in ASC order
+    order: BTreeMap<Rank, K>,
+    counter: u64,
+    max_capacity: usize,
+}
+
+impl<KeyExt, K> PriorityRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K> + Prioritized,
+    K: Hash + Eq + Clone,
+{
+    /// The priority of the key, or `None` if it is not present.
+    pub fn priority(&self, key: &K) -> Option<u64> {
+        self.entries.get(key).map(|entry| entry.rank.0)
+    }
+
+    /// Changes the priority of a present key without adding it again or changing its recency.
+    /// Returns whether the key was present. Takes O(log n).
+    pub fn set_priority(&mut self, key: &K, priority: u64) -> bool {
+        let Some(entry) = self.entries.get_mut(key) else {
+            return false;
+        };
+        if let Some(k) = self.order.remove(&entry.rank) {
+            entry.rank.0 = priority;
+            self.order.insert(entry.rank, k);
+        }
+        true
+    }
+}
+
+impl<KeyExt, K> GetKeyMut<K> for PriorityRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Eq + Hash,
+{
+    /// Takes O(log n) for finding the key and promoting it to the most recently used one
+    /// of its priority.
+    fn get(&mut self, key: &K) -> Option<&K> {
+        let entry = self.entries.get_mut(key)?;
+        if let Some(k) = self.order.remove(&entry.rank) {
+            self.counter += 1;
+            entry.rank.1 = self.counter;
+            self.order.insert(entry.rank, k);
+        }
+        Some(entry.key.key())
+    }
+}
+
+impl<KeyExt, K> KeyRegistry<K> for PriorityRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K> + Prioritized,
+    K: Hash + Eq + PartialEq + Clone,
+{
+    type KeyExtension = KeyExt;
+
+    fn with_capacity(max_capacity: usize) -> Self {
+        Self {
+            entries: HashMap::with_capacity(preallocation(max_capacity)),
+            order: BTreeMap::new(),
+            counter: 0,
+            max_capacity,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Takes O(log n); an already present key takes the new priority and is promoted.
+    fn add_or_update(&mut self, key: Self::KeyExtension) -> Option<K> {
+        let k = key.key().clone();
+        let deleted_key = match self.entries.remove(&k) {
+            Some(entry) => {
+                self.order.remove(&entry.rank);
+                None
+            }
+            None if self.len() >= self.max_capacity => self.order.pop_first().map(|(_, k)| {
+                self.entries.remove(&k);
+                k
+            }),
+            None => None,
+        };
+
+        self.counter += 1;
+        let rank = (key.priority(), self.counter);
+        self.order.insert(rank, k.clone());
+        self.entries.insert(k, PriorityEntry { key, rank });
+        deleted_key
+    }
+
+    /// Takes O(log n).
+    fn try_remove(&mut self, key: &K) -> Option<K> {
+        let entry = self.entries.remove(key)?;
+        self.order.remove(&entry.rank)
+    }
+}
+
+pub type PriorityCache<K, KeyExt, V> = Cache<K, PriorityRegistry<KeyExt, K>, KeyExt, V>;
+pub type ConcurrentPriorityCache<K, KeyExt, V> =
+    ConcurrentCache<K, PriorityRegistry<KeyExt, K>, KeyExt, V>;
+
+impl<K, KeyExt, V> Cache<K, PriorityRegistry<KeyExt, K>, KeyExt, V>
+where
+    K: Hash + Eq + Clone,
+    KeyExt: KeyExtension<K> + Prioritized,
+{
+    /// Changes the priority of a present key without inserting its value again.
+    /// Returns whether the key was present.
+    pub fn set_priority(&mut self, key: &K, priority: u64) -> bool {
+        self.key_registry_mut().set_priority(key, priority)
+    }
+}
+
+impl<K, KeyExt, V> ConcurrentCache<K, PriorityRegistry<KeyExt, K>, KeyExt, V>
+where
+    K: Hash + Eq + Clone,
+    KeyExt: KeyExtension<K> + Prioritized + Clone,
+{
+    /// Changes the priority of a present key without inserting its value again.
+    /// Returns whether the key was present.
+    /// Locks this cache, blocking the current thread until it can be acquired.
+    pub fn set_priority(&self, key: &K, priority: u64) -> bool {
+        let mut guard = self.write();
+        guard.set_priority(key, priority)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::PrioritizedKey;
+
+    #[test]
+    fn priority_cache_init() {
+        let mut cache = PriorityCache::<i32, PrioritizedKey<i32>, String>::new(Some(4));
+        cache.insert(PrioritizedKey::new(1, 0), "How".to_string());
+        cache.insert(PrioritizedKey::new(2, 0), "Hi".to_string());
+        cache.insert(PrioritizedKey::new(3, 0), "Are".to_string());
+        cache.insert(PrioritizedKey::new(4, 0), "You".to_string());
+        cache.insert(PrioritizedKey::new(5, 0), "Doing".to_string());
+        cache.insert(PrioritizedKey::new(2, 0), "How".to_string());
+
+        assert_eq!(cache.len(), 4);
+
+        assert_eq!(cache.get_mut(&1), None);
+        assert_eq!(cache.get_mut(&2).cloned(), Some("How".to_string()));
+        assert_eq!(cache.get_mut(&3).cloned(), Some("Are".to_string()));
+        assert_eq!(cache.get_mut(&4).cloned(), Some("You".to_string()));
+        assert_eq!(cache.get_mut(&5).cloned(), Some("Doing".to_string()));
+    }
+
+    #[test]
+    fn priority_cache_evicts_lowest_priority() {
+        let mut cache = PriorityCache::<i32, PrioritizedKey<i32>, String>::new(Some(3));
+        cache.insert(PrioritizedKey::new(1, 10), "expensive".to_string());
+        cache.insert(PrioritizedKey::new(2, 1), "cheap".to_string());
+        cache.insert(PrioritizedKey::new(3, 1), "cheap".to_string());
+
+        // 2 is used more recently than 3 among the cheap keys
+        cache.get_mut(&2);
+        cache.insert(PrioritizedKey::new(4, 5), "medium".to_string());
+        assert_eq!(cache.get_mut(&3), None);
+
+        // lowering the priority of 1 keeps its value
+        assert!(cache.set_priority(&1, 0));
+        assert!(!cache.set_priority(&3, 0));
+        assert_eq!(cache.key_registry().priority(&1), Some(0));
+        cache.insert(PrioritizedKey::new(5, 5), "medium".to_string());
+        assert_eq!(cache.get_mut(&1), None);
+        assert_eq!(cache.get_mut(&2).cloned(), Some("cheap".to_string()));
+        assert_eq!(cache.len(), 3);
+    }
+
+    #[test]
+    fn concurrent_priority_cache_sets_priority() {
+        let mut cache = ConcurrentPriorityCache::<i32, PrioritizedKey<i32>, String>::new(Some(2));
+        cache.insert(PrioritizedKey::new(1, 10), "How".to_string());
+        cache.insert(PrioritizedKey::new(2, 1), "Are".to_string());
+
+        assert!(cache.set_priority(&1, 0));
+        assert!(!cache.set_priority(&3, 0));
+        cache.insert(PrioritizedKey::new(3, 5), "You".to_string());
+        assert_eq!(cache.get_mut(&1), None);
+        assert_eq!(cache.get_mut(&2).as_deref(), Some(&"Are".to_string()));
+        assert_eq!(cache.len(), 2);
+    }
+}
@@ -1,7 +1,8 @@
 use crate::{
-    cache::{Cache, GetKey, KeyRegistry},
+    cache::{preallocation, Cache, GetKey, KeyRegistry},
     concurrent_cache::ConcurrentCache,
     key::KeyExtension,
+    linked_list::{GenerationalList, Handle},
 };
 use std::{
     collections::{HashMap, VecDeque},
@@ -45,7 +46,7 @@ where
 
     fn with_capacity(max_capacity: usize) -> Self {
         Self {
-            ordered_keys: VecDeque::with_capacity(max_capacity),
+            ordered_keys: VecDeque::with_capacity(preallocation(max_capacity)),
             max_capacity,
             _phantom_data: std::marker::PhantomData::<K>,
         }
@@ -77,7 +78,7 @@ where
         let mut find_index = None;
 
         for (idx, tk) in self.ordered_keys.iter().enumerate() {
-            if &tk.key() == &key {
+            if tk.key() == key {
                 find_index = Some(idx);
                 break;
             }
@@ -95,16 +96,16 @@ pub type QueuedCache<K, KeyExt, V> = Cache<K, QueuedRegistry<KeyExt, K>, KeyExt,
 pub type ConcurrentQueuedCache<K, KeyExt, V> =
     ConcurrentCache<K, QueuedRegistry<KeyExt, K>, KeyExt, V>;
 
-/// Takes O(1) for finding the keys, but higher memory footprint for having the lookup.
+/// Takes O(1) for finding, adding and removing the keys, but higher memory footprint for having
+/// the lookup.
 pub struct QueuedLookupRegistry<KeyExt, K>
 where
     KeyExt: KeyExtension<K>,
 {
-    idx_lookup: HashMap<K, usize>,
-    /// keys ordered by insertion in DESC order, i.e. latest in back, earliest in front (just as for Vec)
-    ordered_keys: VecDeque<KeyExt>,
+    handle_lookup: HashMap<K, Handle>,
+    /// keys ordered by insertion, i.e. latest in front, earliest in back
+    ordered_keys: GenerationalList<KeyExt>,
     max_capacity: usize,
-    _phantom_data: std::marker::PhantomData<K>,
 }
 
 impl<KeyExt, K> QueuedLookupRegistry<KeyExt, K>
@@ -112,51 +113,23 @@ where
     KeyExt: KeyExtension<K>,
     K: Hash + Eq + PartialEq + Clone,
 {
-    fn update_indices(&mut self) {
-        // TODO: if an index was provided, we could just re-index everyhing above/below that idx
-        // TODO: check performance. maybe memory swap below?
-        let key_idx_map_udpated = self
-            .ordered_keys
-            .iter()
-            .enumerate()
-            .map(|(idx, tk)| (tk.key().clone(), idx))
-            .collect();
-        self.idx_lookup = key_idx_map_udpated;
-    }
-
-    // dangerous operation
-    fn update_indices_with_shift(&mut self, shift: usize) {
-        for (_, idx) in self.idx_lookup.iter_mut() {
-            let shifted_idx = *idx - shift;
-            *idx = shifted_idx;
-        }
-    }
-
     fn delete_oldest(&mut self) -> Option<KeyExt> {
-        let first = self.ordered_keys.pop_front();
+        let first = self.ordered_keys.pop_back();
         if let Some(k) = &first {
-            self.idx_lookup.remove(&k.key().clone());
+            self.handle_lookup.remove(k.key());
         }
-        self.update_indices_with_shift(1);
         first
     }
 
     fn remove_key(&mut self, key: &K) -> Option<KeyExt> {
-        let key = self
-            .idx_lookup
-            .remove(key)
-            .and_then(|idx| self.ordered_keys.remove(idx));
-        if key.is_some() {
-            self.update_indices();
-        }
-        key
+        let handle = self.handle_lookup.remove(key)?;
+        self.ordered_keys.remove(handle)
     }
 
     fn insert(&mut self, key: KeyExt) {
-        let len = self.len();
-        self.idx_lookup.insert(key.key().clone(), len);
-        // let timed_key = TimedKey::create_now(key);
-        self.ordered_keys.push_back(key);
+        let k = key.key().clone();
+        let handle = self.ordered_keys.push_front(key);
+        self.handle_lookup.insert(k, handle);
     }
 }
 
@@ -167,12 +140,10 @@ where
 {
     /// Takes O(1) for finding the key.
     fn get(&self, key: &K) -> Option<&K> {
-        self.idx_lookup.get(key).map(|&idx| {
-            let tk = self.ordered_keys[idx].key();
-            if tk == key {
-                tk
-            } else {
-                panic!("invalid state of key registry");
+        self.handle_lookup.get(key).map(|&handle| {
+            match self.ordered_keys.get(handle).map(|tk| tk.key()) {
+                Some(tk) if tk == key => tk,
+                _ => panic!("invalid state of key registry"),
             }
         })
     }
@@ -187,26 +158,25 @@ where
 
     fn with_capacity(max_capacity: usize) -> Self {
         Self {
-            idx_lookup: HashMap::with_capacity(max_capacity),
-            ordered_keys: VecDeque::with_capacity(max_capacity),
+            handle_lookup: HashMap::with_capacity(preallocation(max_capacity)),
+            ordered_keys: GenerationalList::with_capacity(preallocation(max_capacity)),
             max_capacity,
-            _phantom_data: std::marker::PhantomData::<K>,
         }
     }
 
     fn clear(&mut self) {
-        self.idx_lookup.clear();
+        self.handle_lookup.clear();
         self.ordered_keys.clear();
     }
 
     fn len(&self) -> usize {
-        if self.idx_lookup.len() != self.ordered_keys.len() {
+        if self.handle_lookup.len() != self.ordered_keys.len() {
             panic!("invalid state of key registry");
         }
         self.ordered_keys.len()
     }
 
-    // Takes O(n) in case the key is present, or if storage is full, and O(1) otherwise.
+    /// Takes O(1); an already present key is re-inserted as the latest one.
     fn add_or_update(&mut self, key: KeyExt) -> Option<K> {
         self.try_remove(key.key());
 
@@ -220,10 +190,9 @@ where
         deleted_key.map(|tk| tk.key().clone())
     }
 
-    // Takes O(n) for re-ordering the lookup.
+    /// Takes O(1).
     fn try_remove(&mut self, key: &K) -> Option<K> {
-        let key = self.remove_key(key).map(|tk| tk.key().clone());
-        key
+        self.remove_key(key).map(|tk| tk.key().clone())
     }
 }
 
@@ -293,6 +262,22 @@ mod tests {
         assert_eq!(cache.get(&5).cloned(), Some("Doing".to_string()));
     }
 
+    #[test]
+    fn queued_lookup_registry_keeps_order_after_removal() {
+        let mut registry = QueuedLookupRegistry::<i32, i32>::with_capacity(3);
+        registry.add_or_update(1);
+        registry.add_or_update(2);
+        registry.add_or_update(3);
+
+        assert_eq!(registry.try_remove(&2), Some(2));
+        assert_eq!(registry.try_remove(&2), None);
+        assert_eq!(registry.add_or_update(4), None);
+        assert_eq!(registry.add_or_update(5), Some(1));
+        assert_eq!(registry.add_or_update(6), Some(3));
+        assert_eq!(registry.get(&4), Some(&4));
+        assert_eq!(registry.len(), 3);
+    }
+
     #[test]
     fn concurrent_queued_lookup_cache_init() {
         let cache = Arc::new(ConcurrentQueuedLookupCache::<i32, i32, String>::new(Some(
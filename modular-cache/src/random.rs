@@ -0,0 +1,48 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+};
+
+/// Minimal [`xorshift64*`](https://en.wikipedia.org/wiki/Xorshift#xorshift*) generator,
+/// sufficient for picking eviction candidates. Seeded generators yield reproducible sequences.
+#[derive(Debug, Clone)]
+pub(crate) struct XorShiftRng {
+    state: u64,
+}
+
+impl XorShiftRng {
+    /// Mixes the seed by [`splitmix64`](https://prng.di.unimi.it/splitmix64.c), such that
+    /// distinct seeds yield distinct states.
+    pub(crate) fn with_seed(seed: u64) -> Self {
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        // the state must never be zero
+        Self {
+            state: if z == 0 { 0x9E37_79B9_7F4A_7C15 } else { z },
+        }
+    }
+
+    /// Seeds the generator from the process' random hasher keys.
+    pub(crate) fn from_entropy() -> Self {
+        Self::with_seed(RandomState::new().build_hasher().finish())
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// A number in `0..upper`; `upper` must be positive.
+    pub(crate) fn gen_index(&mut self, upper: usize) -> usize {
+        ((self.next_u64() as u128 * upper as u128) >> 64) as usize
+    }
+
+    /// A number in `0.0..1.0`.
+    pub(crate) fn gen_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
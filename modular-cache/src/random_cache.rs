@@ -0,0 +1,138 @@
+use crate::{
+    cache::{Cache, GetKey, KeyRegistry},
+    concurrent_cache::ConcurrentCache,
+    key::KeyExtension,
+    random::XorShiftRng,
+    sampled_slab::SampledSlab,
+};
+use std::hash::Hash;
+
+/// [`Random replacement`](https://en.wikipedia.org/wiki/Cache_replacement_policies#Random_replacement_(RR))
+/// registry, evicting a uniformly chosen key.
+/// Keys are kept in a [`SampledSlab`], such that finding, evicting and removing keys takes O(1).
+#[derive(Debug)]
+pub struct RandomRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+{
+    keys: SampledSlab<KeyExt, K>,
+    rng: XorShiftRng,
+    max_capacity: usize,
+}
+
+impl<KeyExt, K> RandomRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + Clone,
+{
+    /// Creates a registry which evicts reproducibly for the same `seed` and sequence of operations.
+    pub fn with_seed(max_capacity: usize, seed: u64) -> Self {
+        Self::with_rng(max_capacity, XorShiftRng::with_seed(seed))
+    }
+
+    fn with_rng(max_capacity: usize, rng: XorShiftRng) -> Self {
+        Self {
+            keys: SampledSlab::with_capacity(max_capacity),
+            rng,
+            max_capacity,
+        }
+    }
+}
+
+impl<KeyExt, K> GetKey<K> for RandomRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + Clone,
+{
+    /// Takes O(1) for finding the key.
+    fn get(&self, key: &K) -> Option<&K> {
+        self.keys.get(key).map(|tk| tk.key())
+    }
+}
+
+impl<KeyExt, K> KeyRegistry<K> for RandomRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + PartialEq + Clone,
+{
+    type KeyExtension = KeyExt;
+
+    fn with_capacity(max_capacity: usize) -> Self {
+        Self::with_rng(max_capacity, XorShiftRng::from_entropy())
+    }
+
+    fn clear(&mut self) {
+        self.keys.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Takes O(1); an already present key is updated in place.
+    fn add_or_update(&mut self, key: Self::KeyExtension) -> Option<K> {
+        if let Some(tk) = self.keys.get_mut(key.key()) {
+            *tk = key;
+            return None;
+        }
+
+        let deleted_key = if self.len() >= self.max_capacity {
+            let idx = self.keys.sample(&mut self.rng, 1).pop();
+            idx.map(|idx| self.keys.swap_remove(idx).key().clone())
+        } else {
+            None
+        };
+
+        self.keys.push(key);
+        deleted_key
+    }
+
+    /// Takes O(1).
+    fn try_remove(&mut self, key: &K) -> Option<K> {
+        self.keys.remove(key).map(|tk| tk.key().clone())
+    }
+}
+
+pub type RandomCache<K, KeyExt, V> = Cache<K, RandomRegistry<KeyExt, K>, KeyExt, V>;
+pub type ConcurrentRandomCache<K, KeyExt, V> =
+    ConcurrentCache<K, RandomRegistry<KeyExt, K>, KeyExt, V>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn random_cache_init() {
+        let mut cache = RandomCache::<i32, i32, String>::new(Some(4));
+        cache.insert(1, "How".to_string());
+        cache.insert(2, "Hi".to_string());
+        cache.insert(3, "Are".to_string());
+        cache.insert(4, "You".to_string());
+        cache.insert(2, "How".to_string());
+        cache.insert(5, "Doing".to_string());
+
+        assert_eq!(cache.len(), 4);
+        assert_eq!(cache.get(&5).cloned(), Some("Doing".to_string()));
+        assert_eq!((1..=4).filter(|k| cache.get(k).is_some()).count(), 3);
+
+        assert_eq!(cache.remove(&5), Some("Doing".to_string()));
+        assert_eq!(cache.len(), 3);
+    }
+
+    #[test]
+    fn random_registry_is_reproducible_with_seed() {
+        let evictions = |seed| {
+            let mut registry = RandomRegistry::<usize, usize>::with_seed(10, seed);
+            (0..100)
+                .filter_map(|k| registry.add_or_update(k))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(evictions(42).len(), 90);
+        assert_eq!(evictions(42), evictions(42));
+        assert_ne!(evictions(42), evictions(7));
+        // adjacent seeds are mixed into distinct states
+        assert_ne!(evictions(42), evictions(43));
+        assert_ne!(evictions(0), evictions(1));
+    }
+}
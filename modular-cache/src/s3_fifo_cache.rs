@@ -0,0 +1,248 @@
+use crate::{
+    cache::{preallocation, Cache, GetKeyMut, KeyRegistry},
+    concurrent_cache::ConcurrentCache,
+    key::KeyExtension,
+    linked_list::LinkedList,
+};
+use std::{collections::HashMap, hash::Hash};
+
+/// Share of `max_capacity` for the small probationary queue, as proposed in the paper.
+const SMALL_FRACTION: f64 = 0.1;
+/// Saturation of the 2-bit frequency counter.
+const MAX_FREQUENCY: u8 = 3;
+
+#[derive(Debug)]
+struct S3FifoEntry<KeyExt> {
+    key: KeyExt,
+    frequency: u8,
+}
+
+impl<KeyExt> S3FifoEntry<KeyExt> {
+    fn new(key: KeyExt) -> Self {
+        Self { key, frequency: 0 }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Location {
+    Small(usize),
+    Main(usize),
+    Ghost(usize),
+}
+
+/// [`S3-FIFO`](https://s3fifo.com) registry built from three FIFO queues.
+/// New keys enter the small queue S, which quickly demotes keys not accessed again into the
+/// ghost queue G. Accessed keys move on to the main queue M, where they are lazily reinserted
+/// as long as their frequency counter is positive. Keys found in G are directly added to M.
+/// A hit only increments the key's counter, the queues are never reordered on reads.
+/// Takes O(1) for finding and removing keys, and amortized O(1) for evicting keys.
+#[derive(Debug)]
+pub struct S3FifoRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+{
+    lookup: HashMap<K, Location>,
+    /// keys ordered by insertion, i.e. latest in front, earliest in back
+    small: LinkedList<S3FifoEntry<KeyExt>>,
+    /// keys ordered by (re-)insertion, i.e. latest in front, earliest in back
+    main: LinkedList<S3FifoEntry<KeyExt>>,
+    /// ghost keys ordered by eviction from the small queue, i.e. latest in front, earliest in back
+    ghost: LinkedList<K>,
+    small_capacity: usize,
+    ghost_capacity: usize,
+    max_capacity: usize,
+}
+
+impl<KeyExt, K> S3FifoRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + Clone,
+{
+    fn remember(&mut self, k: K) {
+        let idx = self.ghost.push_front(k.clone());
+        self.lookup.insert(k, Location::Ghost(idx));
+        if self.ghost.len() > self.ghost_capacity {
+            if let Some(ghost) = self.ghost.pop_back() {
+                self.lookup.remove(&ghost);
+            }
+        }
+    }
+
+    fn insert_main(&mut self, entry: S3FifoEntry<KeyExt>) {
+        let k = entry.key.key().clone();
+        let idx = self.main.push_front(entry);
+        self.lookup.insert(k, Location::Main(idx));
+    }
+
+    /// Evicts exactly one key, either from S into G or from M.
+    fn evict(&mut self) -> Option<K> {
+        loop {
+            if self.small.len() >= self.small_capacity || self.main.is_empty() {
+                let mut entry = self.small.pop_back()?;
+                if entry.frequency > 0 {
+                    entry.frequency = 0;
+                    self.insert_main(entry);
+                } else {
+                    let k = entry.key.key().clone();
+                    self.remember(k.clone());
+                    return Some(k);
+                }
+            } else {
+                let mut entry = self.main.pop_back()?;
+                if entry.frequency > 0 {
+                    entry.frequency -= 1;
+                    self.insert_main(entry);
+                } else {
+                    self.lookup.remove(entry.key.key());
+                    return Some(entry.key.key().clone());
+                }
+            }
+        }
+    }
+}
+
+impl<KeyExt, K> GetKeyMut<K> for S3FifoRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Eq + Hash,
+{
+    /// Takes O(1) for finding the key and incrementing its frequency counter.
+    fn get(&mut self, key: &K) -> Option<&K> {
+        let entry = match *self.lookup.get(key)? {
+            Location::Small(idx) => self.small.get_mut(idx),
+            Location::Main(idx) => self.main.get_mut(idx),
+            Location::Ghost(_) => return None,
+        };
+        entry.frequency = (entry.frequency + 1).min(MAX_FREQUENCY);
+        Some(entry.key.key())
+    }
+}
+
+impl<KeyExt, K> KeyRegistry<K> for S3FifoRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + PartialEq + Clone,
+{
+    type KeyExtension = KeyExt;
+
+    fn with_capacity(max_capacity: usize) -> Self {
+        let small_capacity = ((max_capacity as f64 * SMALL_FRACTION) as usize).max(1);
+        Self {
+            lookup: HashMap::with_capacity(preallocation(max_capacity)),
+            small: LinkedList::with_capacity(preallocation(small_capacity)),
+            main: LinkedList::with_capacity(preallocation(max_capacity)),
+            ghost: LinkedList::with_capacity(preallocation(max_capacity)),
+            small_capacity,
+            ghost_capacity: max_capacity.saturating_sub(small_capacity).max(1),
+            max_capacity,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.lookup.clear();
+        self.small.clear();
+        self.main.clear();
+        self.ghost.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.small.len() + self.main.len()
+    }
+
+    /// Takes amortized O(1); an already present key is updated in place.
+    fn add_or_update(&mut self, key: Self::KeyExtension) -> Option<K> {
+        let location = self.lookup.get(key.key()).copied();
+        match location {
+            Some(Location::Small(idx)) => {
+                self.small.get_mut(idx).key = key;
+                return None;
+            }
+            Some(Location::Main(idx)) => {
+                self.main.get_mut(idx).key = key;
+                return None;
+            }
+            Some(Location::Ghost(idx)) => {
+                self.ghost.remove(idx);
+                self.lookup.remove(key.key());
+            }
+            None => {}
+        }
+
+        let deleted_key = if self.len() >= self.max_capacity {
+            self.evict()
+        } else {
+            None
+        };
+
+        if let Some(Location::Ghost(_)) = location {
+            self.insert_main(S3FifoEntry::new(key));
+        } else {
+            let k = key.key().clone();
+            let idx = self.small.push_front(S3FifoEntry::new(key));
+            self.lookup.insert(k, Location::Small(idx));
+        }
+        deleted_key
+    }
+
+    /// Takes O(1); ghost keys are not affected.
+    fn try_remove(&mut self, key: &K) -> Option<K> {
+        let entry = match self.lookup.get(key).copied()? {
+            Location::Small(idx) => self.small.remove(idx),
+            Location::Main(idx) => self.main.remove(idx),
+            Location::Ghost(_) => return None,
+        };
+        self.lookup.remove(key);
+        Some(entry.key.key().clone())
+    }
+}
+
+pub type S3FifoCache<K, KeyExt, V> = Cache<K, S3FifoRegistry<KeyExt, K>, KeyExt, V>;
+pub type ConcurrentS3FifoCache<K, KeyExt, V> =
+    ConcurrentCache<K, S3FifoRegistry<KeyExt, K>, KeyExt, V>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn s3_fifo_cache_init() {
+        let mut cache = S3FifoCache::<i32, i32, String>::new(Some(4));
+        cache.insert(1, "How".to_string());
+        cache.insert(2, "Hi".to_string());
+        cache.insert(3, "Are".to_string());
+        cache.insert(4, "You".to_string());
+        cache.insert(5, "Doing".to_string());
+        cache.insert(2, "How".to_string());
+
+        assert_eq!(cache.len(), 4);
+
+        assert_eq!(cache.get_mut(&1), None);
+        assert_eq!(cache.get_mut(&2).cloned(), Some("How".to_string()));
+        assert_eq!(cache.get_mut(&3).cloned(), Some("Are".to_string()));
+        assert_eq!(cache.get_mut(&4).cloned(), Some("You".to_string()));
+        assert_eq!(cache.get_mut(&5).cloned(), Some("Doing".to_string()));
+    }
+
+    #[test]
+    fn s3_fifo_registry_promotes_accessed_and_ghost_keys() {
+        let mut registry = S3FifoRegistry::<i32, i32>::with_capacity(4);
+        registry.add_or_update(1);
+        registry.add_or_update(2);
+        registry.add_or_update(3);
+        registry.add_or_update(4);
+
+        // the accessed 1 moves to the main queue, 2 is demoted into the ghost queue
+        registry.get(&1);
+        assert_eq!(registry.add_or_update(5), Some(2));
+
+        // the ghost 2 directly enters the main queue
+        assert_eq!(registry.add_or_update(2), Some(3));
+        assert_eq!(registry.add_or_update(6), Some(4));
+        assert_eq!(registry.add_or_update(7), Some(5));
+
+        assert_eq!(registry.try_remove(&1), Some(1));
+        assert_eq!(registry.try_remove(&2), Some(2));
+        assert_eq!(registry.try_remove(&3), None);
+        assert_eq!(registry.len(), 2);
+    }
+}
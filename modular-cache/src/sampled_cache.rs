@@ -0,0 +1,360 @@
+use crate::{
+    cache::{preallocation, Cache, GetKeyMut, KeyRegistry},
+    concurrent_cache::ConcurrentCache,
+    key::{Expiring, KeyExtension},
+    random::XorShiftRng,
+    sampled_slab::SampledSlab,
+};
+use std::{hash::Hash, time::Instant};
+
+/// Initial frequency counter of new keys, such that they are not evicted right away.
+const LFU_INIT: u8 = 5;
+/// The larger, the more hits it takes to increment saturated frequency counters.
+const LFU_LOG_FACTOR: f64 = 10.0;
+
+/// Determines the deadline of a key for the volatile-TTL policy.
+pub trait Expirer<KeyExt> {
+    /// The instant the key expires at, or `None` if it never expires.
+    fn deadline(&self, key: &KeyExt) -> Option<Instant>;
+}
+
+/// Lets no key expire, such that the volatile-TTL policy evicts like random sampling.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NeverExpirer;
+
+impl<KeyExt> Expirer<KeyExt> for NeverExpirer {
+    fn deadline(&self, _key: &KeyExt) -> Option<Instant> {
+        None
+    }
+}
+
+/// Takes the deadline from the [`Expiring`] key extension.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct KeyExpirer;
+
+impl<KeyExt> Expirer<KeyExt> for KeyExpirer
+where
+    KeyExt: Expiring,
+{
+    fn deadline(&self, key: &KeyExt) -> Option<Instant> {
+        key.deadline()
+    }
+}
+
+/// Which keys the sampled registry prefers to evict.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampledPolicy {
+    /// approximate LRU, evicting the key which was not used for the longest time
+    Lru,
+    /// approximate LFU, evicting the key with the lowest decaying frequency
+    Lfu,
+    /// evicting the key expiring first as given by the registry's [`Expirer`]; keys which never
+    /// expire are only evicted if no sampled key expires
+    VolatileTtl,
+}
+
+/// Eviction sampling of the sampled registry.
+#[derive(Debug, Clone, Copy)]
+pub struct SampledConfig {
+    pub policy: SampledPolicy,
+    /// number of keys sampled per eviction; all keys are compared if there are not more
+    pub samples: usize,
+    /// number of the best eviction candidates kept between evictions
+    pub pool_size: usize,
+    /// seed for sampling reproducibly, random if `None`
+    pub seed: Option<u64>,
+}
+
+impl Default for SampledConfig {
+    /// The sample and pool size used by Redis.
+    fn default() -> Self {
+        Self {
+            policy: SampledPolicy::Lru,
+            samples: 5,
+            pool_size: 16,
+            seed: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct SampledEntry<KeyExt> {
+    key: KeyExt,
+    /// logical time of the last use
+    stamp: u32,
+    /// logarithmic frequency counter
+    counter: u8,
+}
+
+impl<KeyExt, K> KeyExtension<K> for SampledEntry<KeyExt>
+where
+    KeyExt: KeyExtension<K>,
+{
+    fn key(&self) -> &K {
+        self.key.key()
+    }
+}
+
+/// Registry approximating LRU, LFU or volatile-TTL eviction as
+/// [`Redis`](https://redis.io/docs/reference/eviction/) does. Instead of ordering all keys, it
+/// only keeps a logical timestamp and a logarithmic frequency counter per key in a dense array.
+/// To evict, it samples some keys, merges them into a pool of the best candidates which is kept
+/// between evictions, and evicts the best candidate of the pool.
+/// The frequency counters grow with a probability decreasing in their value and decay by one per
+/// `max_capacity` uses of any key since the key's last use.
+/// Takes O(1) for finding and adding keys, O(pool_size) for removing keys,
+/// and O(samples * pool_size) for evicting keys.
+#[derive(Debug)]
+pub struct SampledRegistry<KeyExt, K, E = NeverExpirer>
+where
+    KeyExt: KeyExtension<K>,
+{
+    entries: SampledSlab<SampledEntry<KeyExt>, K>,
+    /// the best eviction candidates and their scores, in ASC order of the scores
+    pool: Vec<(u64, K)>,
+    rng: XorShiftRng,
+    /// logical time, incremented per use of any key
+    clock: u32,
+    /// reference for the scores of deadlines
+    epoch: Instant,
+    policy: SampledPolicy,
+    expirer: E,
+    samples: usize,
+    pool_size: usize,
+    max_capacity: usize,
+}
+
+impl<KeyExt, K, E> SampledRegistry<KeyExt, K, E>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + Clone,
+    E: Expirer<KeyExt>,
+{
+    pub fn with_config(max_capacity: usize, config: SampledConfig) -> Self
+    where
+        E: Default,
+    {
+        Self::with_expirer(max_capacity, config, E::default())
+    }
+
+    /// Creates a registry taking the deadlines of the volatile-TTL policy from the `expirer`.
+    pub fn with_expirer(max_capacity: usize, config: SampledConfig, expirer: E) -> Self {
+        let rng = match config.seed {
+            Some(seed) => XorShiftRng::with_seed(seed),
+            None => XorShiftRng::from_entropy(),
+        };
+        Self {
+            entries: SampledSlab::with_capacity(max_capacity),
+            pool: Vec::with_capacity(preallocation(config.pool_size)),
+            rng,
+            clock: 0,
+            epoch: Instant::now(),
+            policy: config.policy,
+            expirer,
+            samples: config.samples.max(1),
+            pool_size: config.pool_size.max(1),
+            max_capacity,
+        }
+    }
+
+    /// How much the entry should be evicted; the higher, the sooner.
+    fn score(&self, entry: &SampledEntry<KeyExt>) -> u64 {
+        match self.policy {
+            SampledPolicy::Lru => self.clock.wrapping_sub(entry.stamp) as u64,
+            SampledPolicy::Lfu => (u8::MAX - decayed(entry, self.clock, self.max_capacity)) as u64,
+            SampledPolicy::VolatileTtl => match self.expirer.deadline(&entry.key) {
+                Some(deadline) => {
+                    let millis = deadline.saturating_duration_since(self.epoch).as_millis();
+                    // keys which expire score higher than the ones which never expire
+                    u64::MAX - u64::try_from(millis).unwrap_or(u64::MAX).min(u64::MAX - 1)
+                }
+                None => 0,
+            },
+        }
+    }
+
+    /// Merges the sampled keys into the pool, keeping the ones with the highest scores.
+    fn populate_pool(&mut self) {
+        let candidates = self.entries.sample(&mut self.rng, self.samples);
+        for idx in candidates {
+            let entry = self.entries.at(idx);
+            let score = self.score(entry);
+            let k = entry.key.key();
+            if let Some(pos) = self.pool.iter().position(|(_, p)| p == k) {
+                self.pool.remove(pos);
+            } else if self.pool.len() >= self.pool_size {
+                if self.pool[0].0 >= score {
+                    continue;
+                }
+                self.pool.remove(0);
+            }
+            let pos = self.pool.partition_point(|(s, _)| *s <= score);
+            self.pool.insert(pos, (score, k.clone()));
+        }
+    }
+
+    /// Evicts the best candidate of the pool after refilling it by sampling.
+    fn evict(&mut self) -> Option<K> {
+        self.populate_pool();
+        let (_, k) = self.pool.pop()?;
+        self.entries.remove(&k).map(|entry| entry.key.key().clone())
+    }
+}
+
+/// The entry's frequency counter after decaying it by one per `period` ticks since its last use.
+fn decayed<KeyExt>(entry: &SampledEntry<KeyExt>, clock: u32, period: usize) -> u8 {
+    let periods = clock.wrapping_sub(entry.stamp) as usize / period.max(1);
+    entry
+        .counter
+        .saturating_sub(periods.min(u8::MAX as usize) as u8)
+}
+
+impl<KeyExt, K, E> GetKeyMut<K> for SampledRegistry<KeyExt, K, E>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + Clone,
+{
+    /// Takes O(1) for finding the key and updating its timestamp and frequency counter.
+    fn get(&mut self, key: &K) -> Option<&K> {
+        let entry = self.entries.get_mut(key)?;
+        self.clock = self.clock.wrapping_add(1);
+        let counter = decayed(entry, self.clock, self.max_capacity);
+        let base = counter.saturating_sub(LFU_INIT) as f64;
+        let grows = self.rng.gen_f64() < 1.0 / (base * LFU_LOG_FACTOR + 1.0);
+        entry.counter = if grows {
+            counter.saturating_add(1)
+        } else {
+            counter
+        };
+        entry.stamp = self.clock;
+        Some(entry.key.key())
+    }
+}
+
+impl<KeyExt, K, E> KeyRegistry<K> for SampledRegistry<KeyExt, K, E>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + PartialEq + Clone,
+    E: Expirer<KeyExt> + Default,
+{
+    type KeyExtension = KeyExt;
+
+    fn with_capacity(max_capacity: usize) -> Self {
+        Self::with_config(max_capacity, SampledConfig::default())
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.pool.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Takes O(samples * pool_size) if the registry is full; an already present key is updated
+    /// and used.
+    fn add_or_update(&mut self, key: Self::KeyExtension) -> Option<K> {
+        if let Some(entry) = self.entries.get_mut(key.key()) {
+            let k = key.key().clone();
+            entry.key = key;
+            self.get(&k);
+            return None;
+        }
+
+        let deleted_key = if self.len() >= self.max_capacity {
+            self.evict()
+        } else {
+            None
+        };
+
+        self.clock = self.clock.wrapping_add(1);
+        self.entries.push(SampledEntry {
+            key,
+            stamp: self.clock,
+            counter: LFU_INIT,
+        });
+        deleted_key
+    }
+
+    /// Takes O(pool_size).
+    fn try_remove(&mut self, key: &K) -> Option<K> {
+        let entry = self.entries.remove(key)?;
+        self.pool.retain(|(_, p)| p != key);
+        Some(entry.key.key().clone())
+    }
+}
+
+pub type SampledCache<K, KeyExt, V, E = NeverExpirer> =
+    Cache<K, SampledRegistry<KeyExt, K, E>, KeyExt, V>;
+pub type ConcurrentSampledCache<K, KeyExt, V, E = NeverExpirer> =
+    ConcurrentCache<K, SampledRegistry<KeyExt, K, E>, KeyExt, V>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::{ExpiringKey, TimedKey};
+    use std::time::Duration;
+
+    #[test]
+    fn sampled_cache_init() {
+        let mut cache = SampledCache::<i32, i32, String>::new(Some(4));
+        cache.insert(1, "How".to_string());
+        cache.insert(2, "Hi".to_string());
+        cache.insert(3, "Are".to_string());
+        cache.insert(4, "You".to_string());
+        cache.insert(5, "Doing".to_string());
+        cache.insert(2, "How".to_string());
+
+        assert_eq!(cache.len(), 4);
+
+        // all keys are sampled, such that the least recently used one is evicted
+        assert_eq!(cache.get_mut(&1), None);
+        assert_eq!(cache.get_mut(&2).cloned(), Some("How".to_string()));
+        assert_eq!(cache.get_mut(&3).cloned(), Some("Are".to_string()));
+        assert_eq!(cache.get_mut(&4).cloned(), Some("You".to_string()));
+        assert_eq!(cache.get_mut(&5).cloned(), Some("Doing".to_string()));
+    }
+
+    #[test]
+    fn sampled_cache_accepts_keys_without_deadlines() {
+        let mut cache = SampledCache::<i32, TimedKey<i32>, String>::new(Some(2));
+        cache.insert(TimedKey::create_now(1), "How".to_string());
+        cache.insert(TimedKey::create_now(2), "Are".to_string());
+        cache.get_mut(&1);
+        cache.insert(TimedKey::create_now(3), "You".to_string());
+
+        assert_eq!(cache.get_mut(&2), None);
+        assert_eq!(cache.get_mut(&1).cloned(), Some("How".to_string()));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn sampled_registry_evicts_by_policy() {
+        let config = |policy| SampledConfig {
+            policy,
+            seed: Some(42),
+            ..SampledConfig::default()
+        };
+
+        let mut lfu = SampledRegistry::<i32, i32>::with_config(3, config(SampledPolicy::Lfu));
+        lfu.add_or_update(1);
+        lfu.add_or_update(2);
+        lfu.add_or_update(3);
+        for _ in 0..10 {
+            lfu.get(&1);
+            lfu.get(&3);
+        }
+        assert_eq!(lfu.add_or_update(4), Some(2));
+
+        let mut ttl =
+            SampledRegistry::<_, _, KeyExpirer>::with_config(3, config(SampledPolicy::VolatileTtl));
+        ttl.add_or_update(ExpiringKey::never_expiring(1));
+        ttl.add_or_update(ExpiringKey::expires_in(2, Duration::from_secs(60)));
+        ttl.add_or_update(ExpiringKey::expires_in(3, Duration::from_secs(10)));
+        assert_eq!(ttl.add_or_update(ExpiringKey::never_expiring(4)), Some(3));
+        assert_eq!(ttl.add_or_update(ExpiringKey::never_expiring(5)), Some(2));
+        assert_eq!(ttl.try_remove(&1), Some(1));
+        assert_eq!(ttl.len(), 2);
+    }
+}
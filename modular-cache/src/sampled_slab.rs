@@ -0,0 +1,79 @@
+use crate::{cache::preallocation, key::KeyExtension, random::XorShiftRng};
+use std::{collections::HashMap, hash::Hash};
+
+/// Dense array of entries with a lookup of their indices, the core of the registries evicting
+/// randomly sampled keys. An entry is removed by moving the last entry into its place, such that
+/// it takes O(1) for finding, adding, removing and sampling entries.
+#[derive(Debug)]
+pub(crate) struct SampledSlab<T, K> {
+    idx_lookup: HashMap<K, usize>,
+    entries: Vec<T>,
+}
+
+impl<T, K> SampledSlab<T, K>
+where
+    T: KeyExtension<K>,
+    K: Hash + Eq + Clone,
+{
+    pub(crate) fn with_capacity(max_capacity: usize) -> Self {
+        Self {
+            idx_lookup: HashMap::with_capacity(preallocation(max_capacity)),
+            entries: Vec::with_capacity(preallocation(max_capacity)),
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.idx_lookup.clear();
+        self.entries.clear();
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub(crate) fn get(&self, key: &K) -> Option<&T> {
+        self.idx_lookup.get(key).map(|&idx| &self.entries[idx])
+    }
+
+    pub(crate) fn get_mut(&mut self, key: &K) -> Option<&mut T> {
+        self.idx_lookup.get(key).map(|&idx| &mut self.entries[idx])
+    }
+
+    /// The entry at `idx`, e.g. a sampled one.
+    pub(crate) fn at(&self, idx: usize) -> &T {
+        &self.entries[idx]
+    }
+
+    /// Adds the entry, whose key must not be present.
+    pub(crate) fn push(&mut self, entry: T) {
+        self.idx_lookup
+            .insert(entry.key().clone(), self.entries.len());
+        self.entries.push(entry);
+    }
+
+    pub(crate) fn remove(&mut self, key: &K) -> Option<T> {
+        let idx = *self.idx_lookup.get(key)?;
+        Some(self.swap_remove(idx))
+    }
+
+    /// Up to `samples` indices of entries, drawn uniformly with replacement, or the indices of all
+    /// entries if there are not more.
+    pub(crate) fn sample(&self, rng: &mut XorShiftRng, samples: usize) -> Vec<usize> {
+        let len = self.entries.len();
+        if len <= samples {
+            (0..len).collect()
+        } else {
+            (0..samples).map(|_| rng.gen_index(len)).collect()
+        }
+    }
+
+    /// Removes the entry at `idx` by moving the last entry into its place.
+    pub(crate) fn swap_remove(&mut self, idx: usize) -> T {
+        let entry = self.entries.swap_remove(idx);
+        self.idx_lookup.remove(entry.key());
+        if let Some(moved) = self.entries.get(idx) {
+            self.idx_lookup.insert(moved.key().clone(), idx);
+        }
+        entry
+    }
+}
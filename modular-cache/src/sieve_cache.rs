@@ -0,0 +1,175 @@
+use crate::{
+    cache::{preallocation, Cache, GetKeyMut, KeyRegistry},
+    concurrent_cache::ConcurrentCache,
+    key::KeyExtension,
+    linked_list::LinkedList,
+};
+use std::{collections::HashMap, hash::Hash};
+
+#[derive(Debug)]
+struct SieveEntry<KeyExt> {
+    key: KeyExt,
+    visited: bool,
+}
+
+/// [`SIEVE`](https://cachemon.github.io/SIEVE-website/) registry.
+/// Keys are kept in a FIFO queue and a hit only sets the key's visited bit, it never moves the key.
+/// On eviction the hand moves from the tail (oldest) towards the head (latest), clearing the bits,
+/// and evicts the first key which was not visited; the hand remembers its position.
+/// Takes O(1) for finding and removing keys, and amortized O(1) for evicting keys.
+#[derive(Debug)]
+pub struct SieveRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+{
+    idx_lookup: HashMap<K, usize>,
+    /// keys ordered by insertion, i.e. latest in front, earliest in back
+    ordered_keys: LinkedList<SieveEntry<KeyExt>>,
+    hand: Option<usize>,
+    max_capacity: usize,
+}
+
+impl<KeyExt, K> SieveRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Eq + Hash,
+{
+    fn evict(&mut self) -> Option<KeyExt> {
+        let mut idx = self.hand.or_else(|| self.ordered_keys.back())?;
+        loop {
+            let entry = self.ordered_keys.get_mut(idx);
+            if !entry.visited {
+                break;
+            }
+            entry.visited = false;
+            idx = self
+                .ordered_keys
+                .prev(idx)
+                .or_else(|| self.ordered_keys.back())?;
+        }
+
+        self.hand = self.ordered_keys.prev(idx);
+        let entry = self.ordered_keys.remove(idx);
+        self.idx_lookup.remove(entry.key.key());
+        Some(entry.key)
+    }
+}
+
+impl<KeyExt, K> GetKeyMut<K> for SieveRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Eq + Hash,
+{
+    /// Takes O(1) for finding the key and marking it as visited.
+    fn get(&mut self, key: &K) -> Option<&K> {
+        let idx = *self.idx_lookup.get(key)?;
+        let entry = self.ordered_keys.get_mut(idx);
+        entry.visited = true;
+        Some(entry.key.key())
+    }
+}
+
+impl<KeyExt, K> KeyRegistry<K> for SieveRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + PartialEq + Clone,
+{
+    type KeyExtension = KeyExt;
+
+    fn with_capacity(max_capacity: usize) -> Self {
+        Self {
+            idx_lookup: HashMap::with_capacity(preallocation(max_capacity)),
+            ordered_keys: LinkedList::with_capacity(preallocation(max_capacity)),
+            hand: None,
+            max_capacity,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.idx_lookup.clear();
+        self.ordered_keys.clear();
+        self.hand = None;
+    }
+
+    fn len(&self) -> usize {
+        self.ordered_keys.len()
+    }
+
+    /// Takes O(1) if there is space left; an already present key is updated in place.
+    fn add_or_update(&mut self, key: Self::KeyExtension) -> Option<K> {
+        if let Some(&idx) = self.idx_lookup.get(key.key()) {
+            self.ordered_keys.get_mut(idx).key = key;
+            return None;
+        }
+
+        let deleted_key = if self.len() >= self.max_capacity {
+            self.evict().map(|tk| tk.key().clone())
+        } else {
+            None
+        };
+
+        let k = key.key().clone();
+        let idx = self.ordered_keys.push_front(SieveEntry {
+            key,
+            visited: false,
+        });
+        self.idx_lookup.insert(k, idx);
+        deleted_key
+    }
+
+    /// Takes O(1).
+    fn try_remove(&mut self, key: &K) -> Option<K> {
+        let idx = self.idx_lookup.remove(key)?;
+        if self.hand == Some(idx) {
+            self.hand = self.ordered_keys.prev(idx);
+        }
+        Some(self.ordered_keys.remove(idx).key.key().clone())
+    }
+}
+
+pub type SieveCache<K, KeyExt, V> = Cache<K, SieveRegistry<KeyExt, K>, KeyExt, V>;
+pub type ConcurrentSieveCache<K, KeyExt, V> =
+    ConcurrentCache<K, SieveRegistry<KeyExt, K>, KeyExt, V>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sieve_cache_init() {
+        let mut cache = SieveCache::<i32, i32, String>::new(Some(4));
+        cache.insert(1, "How".to_string());
+        cache.insert(2, "Hi".to_string());
+        cache.insert(3, "Are".to_string());
+        cache.insert(4, "You".to_string());
+        cache.insert(5, "Doing".to_string());
+        cache.insert(2, "How".to_string());
+
+        assert_eq!(cache.len(), 4);
+
+        assert_eq!(cache.get_mut(&1), None);
+        assert_eq!(cache.get_mut(&2).cloned(), Some("How".to_string()));
+        assert_eq!(cache.get_mut(&3).cloned(), Some("Are".to_string()));
+        assert_eq!(cache.get_mut(&4).cloned(), Some("You".to_string()));
+        assert_eq!(cache.get_mut(&5).cloned(), Some("Doing".to_string()));
+    }
+
+    #[test]
+    fn sieve_registry_keeps_visited_keys() {
+        let mut registry = SieveRegistry::<i32, i32>::with_capacity(3);
+        registry.add_or_update(1);
+        registry.add_or_update(2);
+        registry.add_or_update(3);
+
+        registry.get(&1);
+        registry.get(&2);
+        // the hand passes the visited 1 and 2
+        assert_eq!(registry.add_or_update(4), Some(3));
+        // the hand wraps around to the tail, whose bit was cleared by the previous sweep
+        assert_eq!(registry.add_or_update(5), Some(1));
+        assert_eq!(registry.add_or_update(6), Some(2));
+
+        assert_eq!(registry.try_remove(&4), Some(4));
+        assert_eq!(registry.len(), 2);
+    }
+}
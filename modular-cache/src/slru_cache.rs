@@ -0,0 +1,232 @@
+use crate::{
+    cache::{preallocation, Cache, GetKeyMut, KeyRegistry},
+    concurrent_cache::ConcurrentCache,
+    key::KeyExtension,
+    linked_list::LinkedList,
+};
+use std::{collections::HashMap, hash::Hash};
+
+/// Segment sizes of the SLRU registry as fractions of its `max_capacity`.
+#[derive(Debug, Clone, Copy)]
+pub struct SlruConfig {
+    /// size of the protected segment; the probationary segment takes the remaining capacity
+    pub protected_fraction: f64,
+}
+
+impl Default for SlruConfig {
+    fn default() -> Self {
+        Self {
+            protected_fraction: 0.8,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Location {
+    Probation(usize),
+    Protected(usize),
+}
+
+/// [`Segmented LRU`](https://en.wikipedia.org/wiki/Cache_replacement_policies#Segmented_LRU_(SLRU))
+/// registry. New keys enter the probationary segment and a hit promotes them to the protected
+/// segment. If the protected segment overflows, its least recently used key is demoted back to
+/// the probationary segment, from which keys are evicted.
+/// Takes O(1) for finding, promoting and removing keys.
+#[derive(Debug)]
+pub struct SlruRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+{
+    lookup: HashMap<K, Location>,
+    /// keys ordered by recency, i.e. most recently used in front, least recently used in back
+    probation: LinkedList<KeyExt>,
+    /// keys ordered by recency, i.e. most recently used in front, least recently used in back
+    protected: LinkedList<KeyExt>,
+    protected_capacity: usize,
+    max_capacity: usize,
+}
+
+impl<KeyExt, K> SlruRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + Clone,
+{
+    pub fn with_config(max_capacity: usize, config: SlruConfig) -> Self {
+        let protected_capacity = (max_capacity as f64 * config.protected_fraction) as usize;
+        Self {
+            lookup: HashMap::with_capacity(preallocation(max_capacity)),
+            probation: LinkedList::with_capacity(preallocation(
+                max_capacity.saturating_sub(protected_capacity),
+            )),
+            protected: LinkedList::with_capacity(preallocation(protected_capacity)),
+            protected_capacity,
+            max_capacity,
+        }
+    }
+
+    pub(crate) fn contains(&self, key: &K) -> bool {
+        self.lookup.contains_key(key)
+    }
+
+    /// The key which is evicted next, i.e. the least recently used one of the probationary
+    /// segment, or of the protected segment if the former is empty.
+    pub(crate) fn victim(&self) -> Option<&K> {
+        match (self.probation.back(), self.protected.back()) {
+            (Some(idx), _) => Some(self.probation.get(idx).key()),
+            (None, Some(idx)) => Some(self.protected.get(idx).key()),
+            (None, None) => None,
+        }
+    }
+}
+
+impl<KeyExt, K> GetKeyMut<K> for SlruRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Eq + Hash,
+{
+    /// Takes O(1) for finding and promoting the key.
+    fn get(&mut self, key: &K) -> Option<&K> {
+        let location = self.lookup.get_mut(key)?;
+        match *location {
+            Location::Protected(idx) => {
+                self.protected.move_to_front(idx);
+                Some(self.protected.get(idx).key())
+            }
+            Location::Probation(idx) => {
+                let tk = self.probation.remove(idx);
+                let idx = self.protected.push_front(tk);
+                *location = Location::Protected(idx);
+
+                if self.protected.len() > self.protected_capacity {
+                    if let Some(demoted) = self.protected.pop_back() {
+                        let demoted_idx = self.probation.push_front(demoted);
+                        let demoted_key = self.probation.get(demoted_idx).key();
+                        if let Some(l) = self.lookup.get_mut(demoted_key) {
+                            *l = Location::Probation(demoted_idx);
+                        }
+                    }
+                }
+                // the key itself is demoted if the protected segment has no capacity
+                match self.lookup.get(key)? {
+                    Location::Protected(idx) => Some(self.protected.get(*idx).key()),
+                    Location::Probation(idx) => Some(self.probation.get(*idx).key()),
+                }
+            }
+        }
+    }
+}
+
+impl<KeyExt, K> KeyRegistry<K> for SlruRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + PartialEq + Clone,
+{
+    type KeyExtension = KeyExt;
+
+    fn with_capacity(max_capacity: usize) -> Self {
+        Self::with_config(max_capacity, SlruConfig::default())
+    }
+
+    fn clear(&mut self) {
+        self.lookup.clear();
+        self.probation.clear();
+        self.protected.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.probation.len() + self.protected.len()
+    }
+
+    /// Takes O(1); an already present key is updated in place.
+    fn add_or_update(&mut self, key: Self::KeyExtension) -> Option<K> {
+        match self.lookup.get(key.key()).copied() {
+            Some(Location::Probation(idx)) => {
+                *self.probation.get_mut(idx) = key;
+                return None;
+            }
+            Some(Location::Protected(idx)) => {
+                *self.protected.get_mut(idx) = key;
+                return None;
+            }
+            None => {}
+        }
+
+        let deleted_key = if self.len() >= self.max_capacity {
+            self.probation
+                .pop_back()
+                .or_else(|| self.protected.pop_back())
+                .map(|tk| {
+                    self.lookup.remove(tk.key());
+                    tk.key().clone()
+                })
+        } else {
+            None
+        };
+
+        let k = key.key().clone();
+        let idx = self.probation.push_front(key);
+        self.lookup.insert(k, Location::Probation(idx));
+        deleted_key
+    }
+
+    /// Takes O(1).
+    fn try_remove(&mut self, key: &K) -> Option<K> {
+        let tk = match self.lookup.remove(key)? {
+            Location::Probation(idx) => self.probation.remove(idx),
+            Location::Protected(idx) => self.protected.remove(idx),
+        };
+        Some(tk.key().clone())
+    }
+}
+
+pub type SlruCache<K, KeyExt, V> = Cache<K, SlruRegistry<KeyExt, K>, KeyExt, V>;
+pub type ConcurrentSlruCache<K, KeyExt, V> = ConcurrentCache<K, SlruRegistry<KeyExt, K>, KeyExt, V>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slru_cache_init() {
+        let mut cache = SlruCache::<i32, i32, String>::new(Some(4));
+        cache.insert(1, "How".to_string());
+        cache.insert(2, "Hi".to_string());
+        cache.insert(3, "Are".to_string());
+        cache.insert(4, "You".to_string());
+        cache.insert(5, "Doing".to_string());
+        cache.insert(2, "How".to_string());
+
+        assert_eq!(cache.len(), 4);
+
+        assert_eq!(cache.get_mut(&1), None);
+        assert_eq!(cache.get_mut(&2).cloned(), Some("How".to_string()));
+        assert_eq!(cache.get_mut(&3).cloned(), Some("Are".to_string()));
+        assert_eq!(cache.get_mut(&4).cloned(), Some("You".to_string()));
+        assert_eq!(cache.get_mut(&5).cloned(), Some("Doing".to_string()));
+    }
+
+    #[test]
+    fn slru_registry_promotes_and_demotes() {
+        let config = SlruConfig {
+            protected_fraction: 0.5,
+        };
+        let mut registry = SlruRegistry::<i32, i32>::with_config(4, config);
+        for k in 1..=4 {
+            registry.add_or_update(k);
+        }
+
+        // protect 1 and 2, then 3, which demotes 1 back to probation
+        registry.get(&1);
+        registry.get(&2);
+        registry.get(&3);
+
+        // probation holds 1 and 4, with 4 being the least recently used
+        assert_eq!(registry.add_or_update(5), Some(4));
+        assert_eq!(registry.add_or_update(6), Some(1));
+        assert_eq!(registry.add_or_update(7), Some(5));
+
+        assert_eq!(registry.try_remove(&2), Some(2));
+        assert_eq!(registry.try_remove(&3), Some(3));
+        assert_eq!(registry.len(), 2);
+    }
+}
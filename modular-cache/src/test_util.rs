@@ -0,0 +1,19 @@
+use crate::key::TimedKey;
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+/// The instant `offset` before now. If the monotonic clock has not run that long yet, e.g. right
+/// after booting, it waits for `offset` rather than panicking.
+pub(crate) fn ago(offset: Duration) -> Instant {
+    Instant::now().checked_sub(offset).unwrap_or_else(|| {
+        thread::sleep(offset);
+        Instant::now() - offset
+    })
+}
+
+/// A timed key whose instant lies `offset` before now.
+pub(crate) fn timed_key_ago<K>(key: K, offset: Duration) -> TimedKey<K> {
+    TimedKey::with_instant(key, ago(offset))
+}
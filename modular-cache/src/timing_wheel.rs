@@ -0,0 +1,347 @@
+use std::time::{Duration, Instant};
+
+const SLOT_BITS: u32 = 6;
+const SLOTS: usize = 1 << SLOT_BITS;
+/// enough levels to cover every tick of a `u64`
+const LEVELS: usize = 11;
+/// the list of timers which were scheduled with a deadline which already passed
+const OVERDUE: usize = LEVELS * SLOTS;
+
+/// Handle to a scheduled timer. It is tagged with the generation of the timer's slot, such that it
+/// turns stale once the timer fired or was cancelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerHandle {
+    idx: usize,
+    generation: u64,
+}
+
+#[derive(Debug)]
+struct Timer<T> {
+    /// `None` if the slot is vacant
+    value: Option<T>,
+    deadline: u64,
+    /// incremented whenever the slot is vacated
+    generation: u64,
+    /// the list the timer is linked into
+    list: usize,
+    prev: Option<usize>,
+    next: Option<usize>,
+}
+
+/// [`Hierarchical timing wheel`](http://www.cs.columbia.edu/~nahum/w6998/papers/sosp87-timing-wheels.pdf)
+/// for scheduling expirations, e.g. of time-based registries.
+/// Time is divided into ticks; each level of the wheel has 64 slots, where a slot of the lowest
+/// level spans one tick and a slot of each further level spans all slots of the level below.
+/// Timers are kept in the slot of their deadline on the lowest level it differs from the current
+/// tick and cascade to lower levels as time advances, until they fire on the lowest level.
+/// Each level keeps a bitmap of its occupied slots, such that advancing jumps straight to the next
+/// slot holding timers instead of stepping through every elapsed tick.
+/// Takes O(1) for scheduling and cancelling timers, and advancing takes O(1) per processed slot and
+/// fired timer.
+#[derive(Debug)]
+pub struct TimingWheel<T> {
+    start: Instant,
+    tick: Duration,
+    /// the number of ticks which were processed, i.e. all timers due until then fired
+    elapsed: u64,
+    /// the first timer of each slot of each level, followed by the overdue list
+    heads: Vec<Option<usize>>,
+    /// the occupied slots of each level, one bit per slot
+    occupied: [u64; LEVELS],
+    timers: Vec<Timer<T>>,
+    free: Vec<usize>,
+    len: usize,
+}
+
+impl<T> TimingWheel<T> {
+    /// Creates a wheel whose first tick starts at `start` and lasts `tick`, the granularity of
+    /// the deadlines. Timers never fire before their deadline, but up to one tick after it.
+    pub fn new(start: Instant, tick: Duration) -> Self {
+        Self {
+            start,
+            tick: tick.max(Duration::from_nanos(1)),
+            elapsed: 0,
+            heads: vec![None; OVERDUE + 1],
+            occupied: [0; LEVELS],
+            timers: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Cancels all timers, such that all handles turn stale.
+    pub fn clear(&mut self) {
+        self.heads.iter_mut().for_each(|head| *head = None);
+        self.occupied = [0; LEVELS];
+        for idx in 0..self.timers.len() {
+            if self.timers[idx].value.is_some() {
+                self.vacate(idx);
+            }
+        }
+    }
+
+    /// Schedules the `value` to fire at the `deadline`. Takes O(1).
+    pub fn schedule(&mut self, deadline: Instant, value: T) -> TimerHandle {
+        let since_start = deadline.saturating_duration_since(self.start).as_nanos();
+        let deadline = since_start.div_ceil(self.tick.as_nanos());
+        let deadline = u64::try_from(deadline).unwrap_or(u64::MAX);
+
+        let timer = Timer {
+            value: Some(value),
+            deadline,
+            generation: 0,
+            list: OVERDUE,
+            prev: None,
+            next: None,
+        };
+        let idx = match self.free.pop() {
+            Some(idx) => {
+                let generation = self.timers[idx].generation;
+                self.timers[idx] = Timer {
+                    generation,
+                    ..timer
+                };
+                idx
+            }
+            None => {
+                self.timers.push(timer);
+                self.timers.len() - 1
+            }
+        };
+        self.len += 1;
+        self.place(idx);
+
+        TimerHandle {
+            idx,
+            generation: self.timers[idx].generation,
+        }
+    }
+
+    /// Cancels the timer and returns its value, unless it already fired or was cancelled.
+    /// Takes O(1).
+    pub fn cancel(&mut self, handle: TimerHandle) -> Option<T> {
+        let timer = self.timers.get(handle.idx)?;
+        if timer.generation != handle.generation || timer.value.is_none() {
+            return None;
+        }
+        self.unlink(handle.idx);
+        self.vacate(handle.idx)
+    }
+
+    /// Cancels the timer with the earliest deadline and returns its value, where overdue timers
+    /// come first. Takes O(1) per timer sharing the slot of the earliest one.
+    pub fn pop_earliest(&mut self) -> Option<T> {
+        let list = match self.heads[OVERDUE] {
+            Some(_) => OVERDUE,
+            None => {
+                // lower levels hold earlier deadlines, and the occupied slots of each level
+                // lie ahead of its current slot
+                let level = (0..LEVELS).find(|&level| self.occupied[level] != 0)?;
+                level * SLOTS + self.occupied[level].trailing_zeros() as usize
+            }
+        };
+
+        let mut earliest = self.heads[list]?;
+        let mut next = self.timers[earliest].next;
+        while let Some(idx) = next {
+            if self.timers[idx].deadline < self.timers[earliest].deadline {
+                earliest = idx;
+            }
+            next = self.timers[idx].next;
+        }
+        self.unlink(earliest);
+        self.vacate(earliest)
+    }
+
+    /// Advances the wheel to `now` and returns the values of all timers which are due,
+    /// including the ones which were scheduled for a deadline which already passed.
+    pub fn advance(&mut self, now: Instant) -> Vec<T> {
+        let mut fired = Vec::new();
+        self.fire_list(OVERDUE, &mut fired);
+
+        let now = now.saturating_duration_since(self.start).as_nanos() / self.tick.as_nanos();
+        let now = u64::try_from(now).unwrap_or(u64::MAX);
+        while self.elapsed < now {
+            match self.next_event() {
+                Some(tick) if tick <= now => self.elapsed = tick,
+                // no slot holding timers is processed until then
+                _ => {
+                    self.elapsed = now;
+                    break;
+                }
+            }
+            for level in (1..LEVELS).rev() {
+                let shift = SLOT_BITS * level as u32;
+                if self.elapsed & ((1 << shift) - 1) == 0 {
+                    let slot = (self.elapsed >> shift) as usize & (SLOTS - 1);
+                    self.cascade(level * SLOTS + slot, &mut fired);
+                }
+            }
+            let slot = self.elapsed as usize & (SLOTS - 1);
+            self.fire_list(slot, &mut fired);
+        }
+        fired
+    }
+
+    /// The next tick at which a slot holding timers is cascaded or fired.
+    fn next_event(&self) -> Option<u64> {
+        (0..LEVELS)
+            .filter_map(|level| {
+                let shift = SLOT_BITS * level as u32;
+                let current = (self.elapsed >> shift) & (SLOTS as u64 - 1);
+                let ahead = self.occupied[level] & (u64::MAX << current << 1);
+                (ahead != 0).then(|| {
+                    // the tick keeps the digits of the elapsed ticks above the level
+                    let above = shift + SLOT_BITS;
+                    let high = self.elapsed.checked_shr(above).unwrap_or(0);
+                    let high = high.checked_shl(above).unwrap_or(0);
+                    high | (ahead.trailing_zeros() as u64) << shift
+                })
+            })
+            .min()
+    }
+
+    /// Links the timer into the slot of its deadline on the lowest level it differs from the
+    /// elapsed ticks, or into the overdue list if it is due already.
+    fn place(&mut self, idx: usize) {
+        let deadline = self.timers[idx].deadline;
+        let list = if deadline <= self.elapsed {
+            OVERDUE
+        } else {
+            let level = ((63 - (deadline ^ self.elapsed).leading_zeros()) / SLOT_BITS) as usize;
+            let slot = (deadline >> (SLOT_BITS * level as u32)) as usize & (SLOTS - 1);
+            level * SLOTS + slot
+        };
+
+        if list < OVERDUE {
+            self.occupied[list / SLOTS] |= 1 << (list % SLOTS);
+        }
+        let old_head = self.heads[list];
+        let timer = &mut self.timers[idx];
+        timer.list = list;
+        timer.prev = None;
+        timer.next = old_head;
+        if let Some(h) = old_head {
+            self.timers[h].prev = Some(idx);
+        }
+        self.heads[list] = Some(idx);
+    }
+
+    fn unlink(&mut self, idx: usize) {
+        let (list, prev, next) = {
+            let timer = &self.timers[idx];
+            (timer.list, timer.prev, timer.next)
+        };
+        match prev {
+            Some(p) => self.timers[p].next = next,
+            None => self.heads[list] = next,
+        }
+        if let Some(n) = next {
+            self.timers[n].prev = prev;
+        }
+        if self.heads[list].is_none() && list < OVERDUE {
+            self.occupied[list / SLOTS] &= !(1 << (list % SLOTS));
+        }
+    }
+
+    /// Unlinks all timers of the list at once and returns the first one.
+    fn take_list(&mut self, list: usize) -> Option<usize> {
+        if list < OVERDUE {
+            self.occupied[list / SLOTS] &= !(1 << (list % SLOTS));
+        }
+        self.heads[list].take()
+    }
+
+    fn vacate(&mut self, idx: usize) -> Option<T> {
+        let timer = &mut self.timers[idx];
+        timer.generation += 1;
+        self.free.push(idx);
+        self.len -= 1;
+        timer.value.take()
+    }
+
+    /// Re-places all timers of the list relative to the elapsed ticks, firing the due ones.
+    fn cascade(&mut self, list: usize, fired: &mut Vec<T>) {
+        let mut next = self.take_list(list);
+        while let Some(idx) = next {
+            next = self.timers[idx].next;
+            if self.timers[idx].deadline <= self.elapsed {
+                fired.extend(self.vacate(idx));
+            } else {
+                self.place(idx);
+            }
+        }
+    }
+
+    fn fire_list(&mut self, list: usize, fired: &mut Vec<T>) {
+        let mut next = self.take_list(list);
+        while let Some(idx) = next {
+            next = self.timers[idx].next;
+            fired.extend(self.vacate(idx));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timing_wheel_fires_timers_across_levels() {
+        let start = Instant::now();
+        let at = |millis| start + Duration::from_millis(millis);
+        let mut wheel = TimingWheel::new(start, Duration::from_millis(1));
+        wheel.schedule(at(5), "level 0");
+        wheel.schedule(at(100), "level 1");
+        wheel.schedule(at(10_000), "level 2");
+        let cancelled = wheel.schedule(at(2), "cancelled");
+        assert_eq!(wheel.len(), 4);
+
+        assert_eq!(wheel.cancel(cancelled), Some("cancelled"));
+        assert_eq!(wheel.cancel(cancelled), None);
+        assert!(wheel.advance(at(4)).is_empty());
+        assert_eq!(wheel.advance(at(5)), vec!["level 0"]);
+        assert!(wheel.advance(at(99)).is_empty());
+        assert_eq!(wheel.advance(at(100)), vec!["level 1"]);
+        assert_eq!(wheel.advance(at(20_000)), vec!["level 2"]);
+        assert!(wheel.is_empty());
+
+        // overdue timers fire on the next advance, while handles of fired ones are stale
+        let overdue = wheel.schedule(at(1), "overdue");
+        wheel.schedule(at(20_064), "later");
+        assert_eq!(wheel.advance(at(20_000)), vec!["overdue"]);
+        assert_eq!(wheel.cancel(overdue), None);
+        assert_eq!(wheel.advance(at(20_100)), vec!["later"]);
+    }
+
+    #[test]
+    fn timing_wheel_jumps_to_far_deadlines() {
+        let start = Instant::now();
+        let day = Duration::from_secs(24 * 60 * 60);
+        // stepping through each nanosecond tick of a day would never finish
+        let mut wheel = TimingWheel::new(start, Duration::from_nanos(1));
+        wheel.schedule(start + day, "tomorrow");
+        wheel.schedule(start + Duration::from_millis(200), "soon");
+        wheel.schedule(start + 2 * day, "later");
+
+        assert!(wheel.advance(start + Duration::from_millis(199)).is_empty());
+        assert_eq!(wheel.advance(start + day), vec!["soon", "tomorrow"]);
+        assert_eq!(wheel.len(), 1);
+
+        // the earliest timer is found on the lowest occupied level
+        wheel.schedule(start + day + Duration::from_secs(60), "next minute");
+        wheel.schedule(start + day + Duration::from_nanos(100), "next tick");
+        assert_eq!(wheel.pop_earliest(), Some("next tick"));
+        assert_eq!(wheel.pop_earliest(), Some("next minute"));
+        assert_eq!(wheel.advance(start + 3 * day), vec!["later"]);
+        assert_eq!(wheel.pop_earliest(), None);
+    }
+}
@@ -0,0 +1,210 @@
+use crate::{
+    cache::{preallocation, Cache, GetKeyMut, KeyRegistry},
+    concurrent_cache::ConcurrentCache,
+    frequency_sketch::CountMinSketch,
+    key::KeyExtension,
+    linked_list::LinkedList,
+    slru_cache::{SlruConfig, SlruRegistry},
+};
+use std::{collections::HashMap, hash::Hash};
+
+/// [`W-TinyLFU`](https://arxiv.org/abs/1512.00727) registry.
+/// New keys enter a small LRU admission window (1% of `max_capacity`). The window's victim is
+/// admitted to the segmented LRU main space only if a count-min sketch estimates it to be
+/// accessed more frequently than the main space's victim; the loser of this duel is evicted.
+/// Takes O(1) for finding, promoting and removing keys.
+#[derive(Debug)]
+pub struct TinyLfuRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+{
+    /// indices of the window's keys
+    idx_lookup: HashMap<K, usize>,
+    /// keys ordered by recency, i.e. most recently used in front, least recently used in back
+    window: LinkedList<KeyExt>,
+    main: SlruRegistry<KeyExt, K>,
+    sketch: CountMinSketch,
+    window_capacity: usize,
+    main_capacity: usize,
+}
+
+impl<KeyExt, K> TinyLfuRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + Clone,
+{
+    /// Lets the window's victim compete with the main space's victim and returns the loser.
+    fn admit(&mut self, candidate: KeyExt) -> Option<K> {
+        if self.main.len() < self.main_capacity {
+            return self.main.add_or_update(candidate);
+        }
+
+        let victim_frequency = match self.main.victim() {
+            Some(victim) => self.sketch.frequency(victim),
+            None => u8::MAX,
+        };
+        if self.sketch.frequency(candidate.key()) > victim_frequency {
+            // the main space evicts its victim to make room for the candidate
+            self.main.add_or_update(candidate)
+        } else {
+            Some(candidate.key().clone())
+        }
+    }
+}
+
+impl<KeyExt, K> GetKeyMut<K> for TinyLfuRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Eq + Hash,
+{
+    /// Takes O(1) for finding the key, recording the access and promoting the key.
+    fn get(&mut self, key: &K) -> Option<&K> {
+        if let Some(&idx) = self.idx_lookup.get(key) {
+            self.sketch.increment(key);
+            self.window.move_to_front(idx);
+            return Some(self.window.get(idx).key());
+        }
+        let k = self.main.get(key)?;
+        self.sketch.increment(k);
+        Some(k)
+    }
+}
+
+impl<KeyExt, K> KeyRegistry<K> for TinyLfuRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + PartialEq + Clone,
+{
+    type KeyExtension = KeyExt;
+
+    fn with_capacity(max_capacity: usize) -> Self {
+        let window_capacity = (max_capacity / 100).max(1);
+        let main_capacity = max_capacity.saturating_sub(window_capacity);
+        Self {
+            idx_lookup: HashMap::with_capacity(preallocation(window_capacity)),
+            window: LinkedList::with_capacity(preallocation(window_capacity)),
+            main: SlruRegistry::with_config(main_capacity, SlruConfig::default()),
+            sketch: CountMinSketch::with_capacity(max_capacity),
+            window_capacity,
+            main_capacity,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.idx_lookup.clear();
+        self.window.clear();
+        self.main.clear();
+        self.sketch.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.window.len() + self.main.len()
+    }
+
+    /// Takes O(1). Returns either the evicted victim of the main space
+    /// or the window's victim if it was rejected.
+    /// An already present key is updated in place.
+    fn add_or_update(&mut self, key: Self::KeyExtension) -> Option<K> {
+        // widened before counting, as widening resets the counters
+        self.sketch.ensure_capacity(self.len() + 1);
+        self.sketch.increment(key.key());
+        if let Some(&idx) = self.idx_lookup.get(key.key()) {
+            *self.window.get_mut(idx) = key;
+            return None;
+        }
+        if self.main.contains(key.key()) {
+            return self.main.add_or_update(key);
+        }
+
+        let k = key.key().clone();
+        let idx = self.window.push_front(key);
+        self.idx_lookup.insert(k, idx);
+
+        if self.window.len() > self.window_capacity {
+            let candidate = self.window.pop_back()?;
+            self.idx_lookup.remove(candidate.key());
+            return self.admit(candidate);
+        }
+        None
+    }
+
+    /// Takes O(1).
+    fn try_remove(&mut self, key: &K) -> Option<K> {
+        match self.idx_lookup.remove(key) {
+            Some(idx) => Some(self.window.remove(idx).key().clone()),
+            None => self.main.try_remove(key),
+        }
+    }
+}
+
+pub type TinyLfuCache<K, KeyExt, V> = Cache<K, TinyLfuRegistry<KeyExt, K>, KeyExt, V>;
+pub type ConcurrentTinyLfuCache<K, KeyExt, V> =
+    ConcurrentCache<K, TinyLfuRegistry<KeyExt, K>, KeyExt, V>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiny_lfu_cache_init() {
+        let mut cache = TinyLfuCache::<i32, i32, String>::new(Some(4));
+        cache.insert(1, "How".to_string());
+        cache.insert(2, "Hi".to_string());
+        cache.insert(3, "Are".to_string());
+        cache.insert(4, "You".to_string());
+        cache.insert(2, "How".to_string());
+
+        assert_eq!(cache.len(), 4);
+
+        assert_eq!(cache.get_mut(&1).cloned(), Some("How".to_string()));
+        assert_eq!(cache.get_mut(&2).cloned(), Some("How".to_string()));
+        assert_eq!(cache.get_mut(&3).cloned(), Some("Are".to_string()));
+        assert_eq!(cache.get_mut(&4).cloned(), Some("You".to_string()));
+
+        // the window's victim 4 is not used more frequently than the main space's victim 1
+        cache.insert(5, "Doing".to_string());
+        assert_eq!(cache.len(), 4);
+        assert_eq!(cache.get_mut(&4).cloned(), None);
+        assert_eq!(cache.get_mut(&5).cloned(), Some("Doing".to_string()));
+    }
+
+    #[test]
+    fn tiny_lfu_registry_returns_rejected_candidate() {
+        let mut registry = TinyLfuRegistry::<i32, i32>::with_capacity(4);
+        for k in 1..=4 {
+            assert_eq!(registry.add_or_update(k), None);
+        }
+
+        // the window's victim 4 is not used more frequently than the main space's victim 1
+        assert_eq!(registry.add_or_update(5), Some(4));
+        assert_eq!(registry.try_remove(&4), None);
+        assert_eq!(registry.len(), 4);
+
+        // once used more frequently, the window's victim 5 replaces the main space's victim 1
+        registry.get(&5);
+        assert_eq!(registry.add_or_update(6), Some(1));
+        assert_eq!(registry.try_remove(&5), Some(5));
+    }
+
+    #[test]
+    fn tiny_lfu_cache_keeps_hot_keys_during_scan() {
+        let mut cache = TinyLfuCache::<usize, usize, usize>::new(Some(100));
+        for k in 0..10 {
+            cache.insert(k, k);
+        }
+        for _ in 0..10 {
+            for k in 0..10 {
+                cache.get_mut(&k);
+            }
+        }
+
+        for k in 1_000..1_200 {
+            cache.insert(k, k);
+        }
+
+        assert_eq!(cache.len(), 100);
+        for k in 0..10 {
+            assert_eq!(cache.get_mut(&k), Some(&k));
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use crate::{
+    cache::{Cache, GetKeyMut, HouseKeeper, KeyRegistry},
+    concurrent_cache::ConcurrentCache,
+    deadline_queue::DeadlineQueue,
+    key::{KeyExtension, Timed},
+};
+use std::{
+    collections::HashSet,
+    hash::Hash,
+    time::{Duration, Instant},
+};
+
+/// Time-to-idle registry, expiring keys which were not accessed for a fixed duration.
+/// A key's idle time starts at its [`Timed`] instant when it is added and is reset by each `get`.
+/// Idle keys are treated as absent and purged on the next `add_or_update` or housekeeping pass.
+/// If the registry is full, the least recently accessed key is evicted.
+/// Takes O(1) for finding, refreshing, adding and removing keys, and O(1) per purged key.
+#[derive(Debug)]
+pub struct TtiRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+{
+    /// keys ordered by their last access plus the idle timeout
+    queue: DeadlineQueue<KeyExt, K>,
+    idle_timeout: Duration,
+}
+
+impl<KeyExt, K> TtiRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K> + Timed,
+    K: Hash + Eq + Clone,
+{
+    /// Creates a registry expiring keys which were not accessed for `idle_timeout`.
+    pub fn with_idle_timeout(max_capacity: usize, idle_timeout: Duration) -> Self {
+        Self {
+            queue: DeadlineQueue::with_capacity(max_capacity),
+            idle_timeout,
+        }
+    }
+}
+
+impl<KeyExt, K> GetKeyMut<K> for TtiRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Eq + Hash + Clone,
+{
+    /// Takes O(1) for finding the key and resetting its idle time; idle keys are not found.
+    fn get(&mut self, key: &K) -> Option<&K> {
+        self.queue.get(key)?;
+        // keys added with an instant in the future keep their later deadline
+        let refreshed = Instant::now().checked_add(self.idle_timeout);
+        if let (Some(refreshed), Some(deadline)) = (refreshed, self.queue.deadline(key)) {
+            if refreshed > deadline {
+                self.queue.set_deadline(key, Some(refreshed));
+            }
+        }
+        self.queue.get(key)
+    }
+}
+
+impl<KeyExt, K> HouseKeeper<K> for TtiRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K> + Timed,
+    K: Hash + Eq + Clone,
+{
+    /// Purges all idle keys.
+    fn house_keeping(&mut self) -> Option<HashSet<K>> {
+        self.queue.house_keeping()
+    }
+}
+
+impl<KeyExt, K> KeyRegistry<K> for TtiRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K> + Timed,
+    K: Hash + Eq + PartialEq + Clone,
+{
+    type KeyExtension = KeyExt;
+
+    /// Panics, as the registry needs an idle timeout; see `with_idle_timeout` instead.
+    fn with_capacity(_max_capacity: usize) -> Self {
+        panic!("TtiRegistry needs an idle timeout, create it by `with_idle_timeout`");
+    }
+
+    fn clear(&mut self) {
+        self.queue.clear();
+    }
+
+    /// The number of keys, including idle ones which are not purged yet.
+    fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Takes O(1) per purged key; an already present key is accessed again.
+    fn add_or_update(&mut self, key: Self::KeyExtension) -> Option<K> {
+        // an idle timeout exceeding the range of `Instant` never expires
+        let deadline = key.instant().checked_add(self.idle_timeout);
+        self.queue.add_or_update(key, deadline)
+    }
+
+    fn drain_evicted(&mut self) -> Vec<K> {
+        self.queue.drain_evicted()
+    }
+
+    /// Takes O(1); idle keys are removed as well.
+    fn try_remove(&mut self, key: &K) -> Option<K> {
+        self.queue.try_remove(key)
+    }
+}
+
+/// Cache expiring its entries which were not accessed for a fixed duration.
+/// Create it by `TtiCache::with_idle_timeout`; `TtiCache::new` panics, as it lacks the idle
+/// timeout.
+pub type TtiCache<K, KeyExt, V> = Cache<K, TtiRegistry<KeyExt, K>, KeyExt, V>;
+/// Concurrent [`TtiCache`], created by `ConcurrentTtiCache::with_idle_timeout`.
+pub type ConcurrentTtiCache<K, KeyExt, V> = ConcurrentCache<K, TtiRegistry<KeyExt, K>, KeyExt, V>;
+
+impl<K, KeyExt, V> Cache<K, TtiRegistry<KeyExt, K>, KeyExt, V>
+where
+    K: Hash + Eq + Clone,
+    KeyExt: KeyExtension<K> + Timed,
+{
+    /// Creates a cache expiring its entries by the `idle_timeout`.
+    pub fn with_idle_timeout(max_capacity: Option<usize>, idle_timeout: Duration) -> Self {
+        let max_capacity = max_capacity.unwrap_or(usize::MAX);
+        Self::with_registry(TtiRegistry::with_idle_timeout(max_capacity, idle_timeout))
+    }
+}
+
+impl<K, KeyExt, V> ConcurrentCache<K, TtiRegistry<KeyExt, K>, KeyExt, V>
+where
+    K: Hash + Eq + Clone,
+    KeyExt: KeyExtension<K> + Timed + Clone,
+{
+    /// Creates a cache expiring its entries by the `idle_timeout`.
+    pub fn with_idle_timeout(max_capacity: Option<usize>, idle_timeout: Duration) -> Self {
+        let max_capacity = max_capacity.unwrap_or(usize::MAX);
+        Self::with_registry(TtiRegistry::with_idle_timeout(max_capacity, idle_timeout))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::TimedKey;
+    use crate::test_util::timed_key_ago;
+
+    #[test]
+    fn tti_cache_keeps_accessed_keys() {
+        let idle_timeout = Duration::from_secs(10);
+        let mut cache =
+            TtiCache::<i32, TimedKey<i32>, String>::with_idle_timeout(Some(3), idle_timeout);
+        cache.insert(timed_key_ago(1, Duration::from_secs(5)), "How".to_string());
+        cache.insert(timed_key_ago(3, Duration::from_secs(2)), "You".to_string());
+        cache.insert(timed_key_ago(2, Duration::from_secs(20)), "Are".to_string());
+        assert_eq!(cache.get_mut(&2), None);
+        assert_eq!(cache.house_keeping(), 1);
+
+        // accessing 1 resets its idle time, such that it idles for shorter than 3
+        assert_eq!(cache.get_mut(&1).cloned(), Some("How".to_string()));
+        cache.insert(TimedKey::create_now(4), "Doing".to_string());
+        cache.insert(TimedKey::create_now(5), "Today".to_string());
+        assert_eq!(cache.get_mut(&3), None);
+        assert_eq!(cache.get_mut(&1).cloned(), Some("How".to_string()));
+        assert_eq!(cache.len(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "needs an idle timeout")]
+    fn tti_cache_new_requires_idle_timeout() {
+        TtiCache::<i32, TimedKey<i32>, String>::new(Some(2));
+    }
+
+    #[test]
+    fn tti_registry_purges_before_evicting() {
+        let mut registry = TtiRegistry::with_idle_timeout(2, Duration::from_secs(10));
+        registry.add_or_update(timed_key_ago(2, Duration::from_secs(1)));
+        registry.add_or_update(timed_key_ago(1, Duration::from_secs(20)));
+        assert_eq!(registry.get(&1), None);
+
+        // 1 is idle, such that 2 does not need to be evicted
+        let evicted = registry.add_or_update(timed_key_ago(3, Duration::from_secs(1)));
+        assert_eq!(evicted, Some(1));
+        assert!(registry.drain_evicted().is_empty());
+
+        // 2 was accessed more recently than 3
+        registry.get(&2);
+        assert_eq!(registry.add_or_update(TimedKey::create_now(4)), Some(3));
+        assert_eq!(registry.try_remove(&2), Some(2));
+        assert_eq!(registry.len(), 1);
+    }
+}
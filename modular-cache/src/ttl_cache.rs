@@ -0,0 +1,176 @@
+use crate::{
+    cache::{Cache, GetKey, HouseKeeper, KeyRegistry},
+    concurrent_cache::ConcurrentCache,
+    deadline_queue::DeadlineQueue,
+    key::{KeyExtension, Timed},
+};
+use std::{collections::HashSet, hash::Hash, time::Duration};
+
+/// Time-to-live registry, expiring keys a fixed duration after they were written, i.e. after the
+/// [`Timed`] instant of the latest added key. Expired keys are treated as absent and purged on the
+/// next `add_or_update` or housekeeping pass. If the registry is full, the key written first is
+/// evicted.
+/// Takes O(1) for finding, adding and removing keys, and O(1) per purged key.
+#[derive(Debug)]
+pub struct TtlRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+{
+    /// keys ordered by their write instant plus the time-to-live
+    queue: DeadlineQueue<KeyExt, K>,
+    ttl: Duration,
+}
+
+impl<KeyExt, K> TtlRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K> + Timed,
+    K: Hash + Eq + Clone,
+{
+    /// Creates a registry expiring keys `ttl` after they were written.
+    pub fn with_ttl(max_capacity: usize, ttl: Duration) -> Self {
+        Self {
+            queue: DeadlineQueue::with_capacity(max_capacity),
+            ttl,
+        }
+    }
+}
+
+impl<KeyExt, K> GetKey<K> for TtlRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K> + Timed,
+    K: Hash + Eq + Clone,
+{
+    /// Takes O(1) for finding the key; expired keys are not found.
+    fn get(&self, key: &K) -> Option<&K> {
+        self.queue.get(key)
+    }
+}
+
+impl<KeyExt, K> HouseKeeper<K> for TtlRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K> + Timed,
+    K: Hash + Eq + Clone,
+{
+    /// Purges all expired keys.
+    fn house_keeping(&mut self) -> Option<HashSet<K>> {
+        self.queue.house_keeping()
+    }
+}
+
+impl<KeyExt, K> KeyRegistry<K> for TtlRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K> + Timed,
+    K: Hash + Eq + PartialEq + Clone,
+{
+    type KeyExtension = KeyExt;
+
+    /// Panics, as the registry needs a time-to-live; see `with_ttl` instead.
+    fn with_capacity(_max_capacity: usize) -> Self {
+        panic!("TtlRegistry needs a time-to-live, create it by `with_ttl`");
+    }
+
+    fn clear(&mut self) {
+        self.queue.clear();
+    }
+
+    /// The number of keys, including expired ones which are not purged yet.
+    fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Takes O(1) per purged key; an already present key is written again.
+    fn add_or_update(&mut self, key: Self::KeyExtension) -> Option<K> {
+        // a time-to-live exceeding the range of `Instant` never expires
+        let deadline = key.instant().checked_add(self.ttl);
+        self.queue.add_or_update(key, deadline)
+    }
+
+    fn drain_evicted(&mut self) -> Vec<K> {
+        self.queue.drain_evicted()
+    }
+
+    /// Takes O(1); expired keys are removed as well.
+    fn try_remove(&mut self, key: &K) -> Option<K> {
+        self.queue.try_remove(key)
+    }
+}
+
+/// Cache expiring its entries a fixed duration after they were written.
+/// Create it by `TtlCache::with_ttl`; `TtlCache::new` panics, as it lacks the time-to-live.
+pub type TtlCache<K, KeyExt, V> = Cache<K, TtlRegistry<KeyExt, K>, KeyExt, V>;
+/// Concurrent [`TtlCache`], created by `ConcurrentTtlCache::with_ttl`.
+pub type ConcurrentTtlCache<K, KeyExt, V> = ConcurrentCache<K, TtlRegistry<KeyExt, K>, KeyExt, V>;
+
+impl<K, KeyExt, V> Cache<K, TtlRegistry<KeyExt, K>, KeyExt, V>
+where
+    K: Hash + Eq + Clone,
+    KeyExt: KeyExtension<K> + Timed,
+{
+    /// Creates a cache expiring its entries by the `ttl`.
+    pub fn with_ttl(max_capacity: Option<usize>, ttl: Duration) -> Self {
+        let max_capacity = max_capacity.unwrap_or(usize::MAX);
+        Self::with_registry(TtlRegistry::with_ttl(max_capacity, ttl))
+    }
+}
+
+impl<K, KeyExt, V> ConcurrentCache<K, TtlRegistry<KeyExt, K>, KeyExt, V>
+where
+    K: Hash + Eq + Clone,
+    KeyExt: KeyExtension<K> + Timed + Clone,
+{
+    /// Creates a cache expiring its entries by the `ttl`.
+    pub fn with_ttl(max_capacity: Option<usize>, ttl: Duration) -> Self {
+        let max_capacity = max_capacity.unwrap_or(usize::MAX);
+        Self::with_registry(TtlRegistry::with_ttl(max_capacity, ttl))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::TimedKey;
+    use crate::test_util::timed_key_ago;
+
+    #[test]
+    fn ttl_cache_hides_expired_keys() {
+        let mut cache =
+            TtlCache::<i32, TimedKey<i32>, String>::with_ttl(Some(4), Duration::from_secs(10));
+        cache.insert(TimedKey::create_now(2), "Are".to_string());
+        cache.insert(timed_key_ago(1, Duration::from_secs(20)), "How".to_string());
+
+        assert_eq!(cache.get(&1), None);
+        assert_eq!(cache.get(&2).cloned(), Some("Are".to_string()));
+
+        // the expired key is only purged by the housekeeping
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.house_keeping(), 1);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.house_keeping(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "needs a time-to-live")]
+    fn ttl_cache_new_requires_ttl() {
+        TtlCache::<i32, TimedKey<i32>, String>::new(Some(2));
+    }
+
+    #[test]
+    fn ttl_registry_purges_before_evicting() {
+        let mut registry = TtlRegistry::with_ttl(2, Duration::from_secs(10));
+        registry.add_or_update(timed_key_ago(2, Duration::from_secs(5)));
+        registry.add_or_update(timed_key_ago(1, Duration::from_secs(20)));
+
+        // 1 is expired, such that 2 does not need to be evicted
+        assert_eq!(registry.add_or_update(TimedKey::create_now(3)), Some(1));
+        assert!(registry.drain_evicted().is_empty());
+        // 2 was written first
+        assert_eq!(registry.add_or_update(TimedKey::create_now(4)), Some(2));
+
+        // writing a key again renews it
+        registry.add_or_update(timed_key_ago(3, Duration::from_secs(20)));
+        assert_eq!(registry.get(&3), None);
+        assert_eq!(registry.add_or_update(TimedKey::create_now(3)), None);
+        assert_eq!(registry.get(&3), Some(&3));
+        assert_eq!(registry.len(), 2);
+    }
+}
@@ -0,0 +1,255 @@
+use crate::{
+    cache::{preallocation, Cache, GetKeyMut, KeyRegistry},
+    concurrent_cache::ConcurrentCache,
+    key::KeyExtension,
+    linked_list::LinkedList,
+};
+use std::{collections::HashMap, hash::Hash};
+
+/// Sizes of the 2Q queues as fractions of the registry's `max_capacity`, each in `(0, 1]`.
+/// The main queue Am has no fraction of its own: it takes all capacity which A1in does not hold,
+/// i.e. A1in only gives up keys while it exceeds its fraction, and Am gives up keys otherwise.
+#[derive(Debug, Clone, Copy)]
+pub struct TwoQueueConfig {
+    /// size of the FIFO admission queue A1in
+    pub a1in_fraction: f64,
+    /// number of ghost keys remembered in A1out, which do not count towards `max_capacity`
+    pub a1out_fraction: f64,
+}
+
+impl Default for TwoQueueConfig {
+    /// The sizes recommended by the 2Q paper.
+    fn default() -> Self {
+        Self {
+            a1in_fraction: 0.25,
+            a1out_fraction: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Location {
+    A1in(usize),
+    A1out(usize),
+    Am(usize),
+}
+
+/// [`2Q`](https://www.vldb.org/conf/1994/P439.PDF) registry.
+/// New keys are admitted to the FIFO queue A1in; keys evicted from there are remembered in the
+/// ghost queue A1out and only enter the main LRU queue Am when they are added again.
+/// Hence a single scan through many keys does not flush the frequently used ones out of Am.
+/// Takes O(1) for finding, promoting and removing keys.
+#[derive(Debug)]
+pub struct TwoQueueRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+{
+    lookup: HashMap<K, Location>,
+    /// keys ordered by insertion, i.e. latest in front, earliest in back
+    a1in: LinkedList<KeyExt>,
+    /// ghost keys ordered by eviction from a1in, i.e. latest in front, earliest in back
+    a1out: LinkedList<K>,
+    /// keys ordered by recency, i.e. most recently used in front, least recently used in back
+    am: LinkedList<KeyExt>,
+    a1in_capacity: usize,
+    a1out_capacity: usize,
+    max_capacity: usize,
+}
+
+impl<KeyExt, K> TwoQueueRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + Clone,
+{
+    /// Panics if a fraction of the `config` is not in `(0, 1]`.
+    pub fn with_config(max_capacity: usize, config: TwoQueueConfig) -> Self {
+        for (name, fraction) in [
+            ("a1in_fraction", config.a1in_fraction),
+            ("a1out_fraction", config.a1out_fraction),
+        ] {
+            assert!(
+                0.0 < fraction && fraction <= 1.0,
+                "{name} must be in (0, 1], but is {fraction}"
+            );
+        }
+        let fraction_of = |fraction: f64| ((max_capacity as f64 * fraction) as usize).max(1);
+        Self {
+            lookup: HashMap::with_capacity(preallocation(max_capacity)),
+            a1in: LinkedList::with_capacity(preallocation(max_capacity)),
+            a1out: LinkedList::with_capacity(preallocation(max_capacity)),
+            am: LinkedList::with_capacity(preallocation(max_capacity)),
+            a1in_capacity: fraction_of(config.a1in_fraction),
+            a1out_capacity: fraction_of(config.a1out_fraction),
+            max_capacity,
+        }
+    }
+
+    /// Frees a slot if the registry is full, preferably from A1in.
+    fn reclaim(&mut self) -> Option<K> {
+        if self.len() < self.max_capacity {
+            return None;
+        }
+
+        if self.a1in.len() > self.a1in_capacity || self.am.is_empty() {
+            let k = self.a1in.pop_back()?.key().clone();
+            let idx = self.a1out.push_front(k.clone());
+            self.lookup.insert(k.clone(), Location::A1out(idx));
+            if self.a1out.len() > self.a1out_capacity {
+                if let Some(ghost) = self.a1out.pop_back() {
+                    self.lookup.remove(&ghost);
+                }
+            }
+            Some(k)
+        } else {
+            let tk = self.am.pop_back()?;
+            self.lookup.remove(tk.key());
+            Some(tk.key().clone())
+        }
+    }
+}
+
+impl<KeyExt, K> GetKeyMut<K> for TwoQueueRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Eq + Hash,
+{
+    /// Takes O(1); keys in Am are promoted, keys in A1in keep their position.
+    fn get(&mut self, key: &K) -> Option<&K> {
+        match *self.lookup.get(key)? {
+            Location::A1in(idx) => Some(self.a1in.get(idx).key()),
+            Location::Am(idx) => {
+                self.am.move_to_front(idx);
+                Some(self.am.get(idx).key())
+            }
+            Location::A1out(_) => None,
+        }
+    }
+}
+
+impl<KeyExt, K> KeyRegistry<K> for TwoQueueRegistry<KeyExt, K>
+where
+    KeyExt: KeyExtension<K>,
+    K: Hash + Eq + PartialEq + Clone,
+{
+    type KeyExtension = KeyExt;
+
+    fn with_capacity(max_capacity: usize) -> Self {
+        Self::with_config(max_capacity, TwoQueueConfig::default())
+    }
+
+    fn clear(&mut self) {
+        self.lookup.clear();
+        self.a1in.clear();
+        self.a1out.clear();
+        self.am.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.a1in.len() + self.am.len()
+    }
+
+    /// Takes O(1). Keys remembered in A1out enter Am, unknown keys enter A1in.
+    fn add_or_update(&mut self, key: Self::KeyExtension) -> Option<K> {
+        let location = self.lookup.get(key.key()).copied();
+        let deleted_key = match location {
+            Some(Location::A1in(idx)) => {
+                *self.a1in.get_mut(idx) = key;
+                return None;
+            }
+            Some(Location::Am(idx)) => {
+                *self.am.get_mut(idx) = key;
+                self.am.move_to_front(idx);
+                return None;
+            }
+            Some(Location::A1out(idx)) => {
+                self.a1out.remove(idx);
+                self.reclaim()
+            }
+            None => self.reclaim(),
+        };
+
+        let k = key.key().clone();
+        let location = match location {
+            Some(Location::A1out(_)) => Location::Am(self.am.push_front(key)),
+            _ => Location::A1in(self.a1in.push_front(key)),
+        };
+        self.lookup.insert(k, location);
+        deleted_key
+    }
+
+    /// Takes O(1); ghost keys are not affected.
+    fn try_remove(&mut self, key: &K) -> Option<K> {
+        let tk = match self.lookup.get(key).copied()? {
+            Location::A1in(idx) => self.a1in.remove(idx),
+            Location::Am(idx) => self.am.remove(idx),
+            Location::A1out(_) => return None,
+        };
+        self.lookup.remove(key);
+        Some(tk.key().clone())
+    }
+}
+
+pub type TwoQueueCache<K, KeyExt, V> = Cache<K, TwoQueueRegistry<KeyExt, K>, KeyExt, V>;
+pub type ConcurrentTwoQueueCache<K, KeyExt, V> =
+    ConcurrentCache<K, TwoQueueRegistry<KeyExt, K>, KeyExt, V>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_queue_cache_init() {
+        let mut cache = TwoQueueCache::<i32, i32, String>::new(Some(4));
+        cache.insert(1, "How".to_string());
+        cache.insert(2, "Hi".to_string());
+        cache.insert(3, "Are".to_string());
+        cache.insert(4, "You".to_string());
+        cache.insert(5, "Doing".to_string());
+        cache.insert(2, "How".to_string());
+
+        assert_eq!(cache.len(), 4);
+
+        assert_eq!(cache.get_mut(&1), None);
+        assert_eq!(cache.get_mut(&2).cloned(), Some("How".to_string()));
+        assert_eq!(cache.get_mut(&3).cloned(), Some("Are".to_string()));
+        assert_eq!(cache.get_mut(&4).cloned(), Some("You".to_string()));
+        assert_eq!(cache.get_mut(&5).cloned(), Some("Doing".to_string()));
+    }
+
+    #[test]
+    fn two_queue_cache_is_scan_resistant() {
+        let registry = TwoQueueRegistry::with_config(
+            4,
+            TwoQueueConfig {
+                a1in_fraction: 0.25,
+                a1out_fraction: 0.5,
+            },
+        );
+        let mut cache = TwoQueueCache::<i32, i32, String>::with_registry(registry);
+        for k in 1..=5 {
+            cache.insert(k, "Scan".to_string());
+        }
+        // 1 was evicted from A1in and is now remembered in A1out
+        assert_eq!(cache.get_mut(&1), None);
+        cache.insert(1, "Hot".to_string());
+
+        for k in 100..110 {
+            cache.insert(k, "Scan".to_string());
+        }
+
+        assert_eq!(cache.len(), 4);
+        assert_eq!(cache.get_mut(&1).cloned(), Some("Hot".to_string()));
+        assert_eq!(cache.remove(&1), Some("Hot".to_string()));
+        assert_eq!(cache.len(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "a1in_fraction must be in (0, 1], but is NaN")]
+    fn two_queue_registry_rejects_invalid_fractions() {
+        let config = TwoQueueConfig {
+            a1in_fraction: f64::NAN,
+            ..TwoQueueConfig::default()
+        };
+        TwoQueueRegistry::<i32, i32>::with_config(4, config);
+    }
+}